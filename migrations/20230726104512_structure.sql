@@ -0,0 +1,10 @@
+-- Add migration script here
+--
+-- Adds the versions of the tokens, which are bumped to revoke every session
+-- of a user.
+
+ALTER TABLE `normal_users`
+  ADD COLUMN `token_version` int(10) unsigned NOT NULL DEFAULT 0 AFTER `refresh_token`;
+
+ALTER TABLE `senior_users`
+  ADD COLUMN `token_version` int(10) unsigned NOT NULL DEFAULT 0 AFTER `refresh_token`;
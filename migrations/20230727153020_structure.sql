@@ -0,0 +1,15 @@
+-- Add migration script here
+--
+-- Adds the favorite seniors of the normal users.
+
+CREATE TABLE `favorites` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `normal_user_id` int(10) unsigned NOT NULL,
+  `senior_user_id` int(10) unsigned NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `unique_index` (`normal_user_id`,`senior_user_id`),
+  KEY `fk_favorites_senior_user_id` (`senior_user_id`),
+  CONSTRAINT `fk_favorites_normal_user_id` FOREIGN KEY (`normal_user_id`) REFERENCES `normal_users` (`id`) ON DELETE CASCADE,
+  CONSTRAINT `fk_favorites_senior_user_id` FOREIGN KEY (`senior_user_id`) REFERENCES `senior_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
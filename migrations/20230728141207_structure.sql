@@ -0,0 +1,27 @@
+-- Add migration script here
+--
+-- Adds the blocks and the reports between the users.
+
+CREATE TABLE `user_blocks` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `user_type` varchar(16) NOT NULL,
+  `user_id` int(10) unsigned NOT NULL,
+  `blocked_user_type` varchar(16) NOT NULL,
+  `blocked_user_id` int(10) unsigned NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `unique_index` (`user_type`,`user_id`,`blocked_user_type`,`blocked_user_id`),
+  KEY `blocked_user_index` (`blocked_user_type`,`blocked_user_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `user_reports` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `reporter_type` varchar(16) NOT NULL,
+  `reporter_id` int(10) unsigned NOT NULL,
+  `reported_type` varchar(16) NOT NULL,
+  `reported_id` int(10) unsigned NOT NULL,
+  `reason` varchar(1024) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  KEY `reported_user_index` (`reported_type`,`reported_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,14 @@
+-- Add migration script here
+--
+-- Counts the views of the senior profiles in total and per day.
+
+ALTER TABLE `senior_users`
+  ADD COLUMN `view_count` int(10) unsigned NOT NULL DEFAULT 0 AFTER `description`;
+
+CREATE TABLE `senior_daily_views` (
+  `senior_user_id` int(10) unsigned NOT NULL,
+  `view_date` date NOT NULL,
+  `view_count` int(10) unsigned NOT NULL DEFAULT 0,
+  PRIMARY KEY (`senior_user_id`,`view_date`),
+  CONSTRAINT `fk_senior_daily_views_senior_user_id` FOREIGN KEY (`senior_user_id`) REFERENCES `senior_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,9 @@
+-- Add migration script here
+--
+-- Adds the soft deletion of the accounts.
+
+ALTER TABLE `normal_users`
+  ADD COLUMN `deleted_at` timestamp NULL DEFAULT NULL AFTER `updated_at`;
+
+ALTER TABLE `senior_users`
+  ADD COLUMN `deleted_at` timestamp NULL DEFAULT NULL AFTER `updated_at`;
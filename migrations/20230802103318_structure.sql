@@ -0,0 +1,18 @@
+-- Add migration script here
+--
+-- Makes the nicknames unique. The nicknames taken by more than one user so
+-- far are kept by the earliest user, and the others get their id appended.
+
+UPDATE `normal_users` u
+JOIN (SELECT `nickname`, MIN(`id`) AS `first_id` FROM `normal_users` GROUP BY `nickname` HAVING COUNT(*) > 1) d
+  ON d.`nickname` = u.`nickname` AND d.`first_id` <> u.`id`
+SET u.`nickname` = CONCAT(LEFT(u.`nickname`, 22), u.`id`);
+
+UPDATE `senior_users` u
+JOIN (SELECT `nickname`, MIN(`id`) AS `first_id` FROM `senior_users` GROUP BY `nickname` HAVING COUNT(*) > 1) d
+  ON d.`nickname` = u.`nickname` AND d.`first_id` <> u.`id`
+SET u.`nickname` = CONCAT(LEFT(u.`nickname`, 22), u.`id`);
+
+ALTER TABLE `normal_users` ADD UNIQUE KEY `nickname_unique` (`nickname`);
+
+ALTER TABLE `senior_users` ADD UNIQUE KEY `nickname_unique` (`nickname`);
@@ -0,0 +1,20 @@
+-- Add migration script here
+--
+-- Normalizes the phone numbers of the seniors to the E.164 format and makes
+-- them unique.
+
+UPDATE `senior_users`
+SET `phone` = REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(`phone`, ' ', ''), '-', ''), '.', ''), '(', ''), ')', '');
+
+UPDATE `senior_users` SET `phone` = CONCAT('+', `phone`) WHERE `phone` LIKE '82%';
+UPDATE `senior_users` SET `phone` = CONCAT('+82', SUBSTRING(`phone`, 2)) WHERE `phone` LIKE '0%';
+UPDATE `senior_users` SET `phone` = CONCAT('+82', SUBSTRING(`phone`, 5)) WHERE `phone` LIKE '+820%';
+
+-- A number registered by more than one senior is kept by the earliest one.
+-- The others get an invalid number and have to enter theirs again.
+UPDATE `senior_users` u
+JOIN (SELECT `phone`, MIN(`id`) AS `first_id` FROM `senior_users` GROUP BY `phone` HAVING COUNT(*) > 1) d
+  ON d.`phone` = u.`phone` AND d.`first_id` <> u.`id`
+SET u.`phone` = CONCAT(LEFT(u.`phone`, 20), '#', u.`id`);
+
+ALTER TABLE `senior_users` ADD UNIQUE KEY `phone_unique` (`phone`);
@@ -0,0 +1,19 @@
+-- Add migration script here
+--
+-- Adds the verification of the phone numbers of the seniors by SMS.
+
+ALTER TABLE `senior_users`
+  ADD COLUMN `phone_verified` tinyint(1) NOT NULL DEFAULT 0 AFTER `phone`;
+
+CREATE TABLE `phone_verifications` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `senior_user_id` int(10) unsigned NOT NULL,
+  `phone` varchar(32) NOT NULL,
+  `code` varchar(8) NOT NULL,
+  `attempts` int(10) unsigned NOT NULL DEFAULT 0,
+  `expires_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `unique_index` (`senior_user_id`),
+  CONSTRAINT `fk_phone_verifications_senior_user_id` FOREIGN KEY (`senior_user_id`) REFERENCES `senior_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
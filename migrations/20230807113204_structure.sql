@@ -0,0 +1,21 @@
+-- Add migration script here
+--
+-- Allows a pending code for each purpose, and adds the audit logs of the
+-- account recovery. The pending codes so far are for verifying the phones.
+
+ALTER TABLE `phone_verifications`
+  ADD COLUMN `purpose` varchar(32) NOT NULL DEFAULT 'PhoneVerification' AFTER `senior_user_id`,
+  DROP INDEX `unique_index`,
+  ADD UNIQUE KEY `unique_index` (`senior_user_id`,`purpose`);
+
+ALTER TABLE `phone_verifications` ALTER COLUMN `purpose` DROP DEFAULT;
+
+CREATE TABLE `recovery_audit_logs` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `senior_user_id` int(10) unsigned NOT NULL,
+  `event` varchar(32) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  KEY `fk_recovery_audit_logs_senior_user_id` (`senior_user_id`),
+  CONSTRAINT `fk_recovery_audit_logs_senior_user_id` FOREIGN KEY (`senior_user_id`) REFERENCES `senior_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,15 @@
+-- Add migration script here
+--
+-- Adds the consents of the users to the terms and the policies.
+
+CREATE TABLE `consents` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `user_type` varchar(16) NOT NULL,
+  `user_id` int(10) unsigned NOT NULL,
+  `consent_type` varchar(32) NOT NULL,
+  `version` int(10) unsigned NOT NULL,
+  `agreed` tinyint(1) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `unique_index` (`user_type`,`user_id`,`consent_type`,`version`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,34 @@
+-- Add migration script here
+--
+-- Adds the dormant accounts, whose personal data is kept apart. The existing
+-- users count as logged in when the migration runs, so that nobody becomes
+-- dormant right away.
+
+ALTER TABLE `normal_users`
+  ADD COLUMN `last_login_at` timestamp NOT NULL DEFAULT current_timestamp() AFTER `token_version`,
+  ADD COLUMN `dormant_at` timestamp NULL DEFAULT NULL AFTER `deleted_at`;
+
+ALTER TABLE `senior_users`
+  ADD COLUMN `last_login_at` timestamp NOT NULL DEFAULT current_timestamp() AFTER `token_version`,
+  ADD COLUMN `dormant_at` timestamp NULL DEFAULT NULL AFTER `deleted_at`;
+
+CREATE TABLE `dormant_normal_users` (
+  `normal_user_id` int(10) unsigned NOT NULL,
+  `oauth_id` varchar(64) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`normal_user_id`),
+  KEY `oauth_id_index` (`oauth_id`),
+  CONSTRAINT `fk_dormant_normal_users_normal_user_id` FOREIGN KEY (`normal_user_id`) REFERENCES `normal_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+CREATE TABLE `dormant_senior_users` (
+  `senior_user_id` int(10) unsigned NOT NULL,
+  `email` varchar(128) NOT NULL,
+  `name` varchar(8) NOT NULL,
+  `phone` varchar(32) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`senior_user_id`),
+  UNIQUE KEY `email_unique` (`email`),
+  UNIQUE KEY `phone_unique` (`phone`),
+  CONSTRAINT `fk_dormant_senior_users_senior_user_id` FOREIGN KEY (`senior_user_id`) REFERENCES `senior_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
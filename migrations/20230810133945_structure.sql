@@ -0,0 +1,29 @@
+-- Add migration script here
+--
+-- Makes room for the encrypted phone numbers and their keyed hashes, which
+-- take over the uniqueness of the numbers. The key is only known to the
+-- server, so the existing numbers are encrypted by the server when it starts
+-- (see `encrypt_plain_phones`), and are told apart by the missing hash.
+
+ALTER TABLE `senior_users`
+  MODIFY COLUMN `phone` varchar(128) NOT NULL,
+  ADD COLUMN `phone_hash` varchar(64) DEFAULT NULL AFTER `phone`,
+  DROP INDEX `phone_unique`,
+  ADD UNIQUE KEY `phone_unique` (`phone_hash`);
+
+-- The dormant seniors only have a placeholder, which is not encrypted
+UPDATE `senior_users` SET `phone_hash` = `phone` WHERE `dormant_at` IS NOT NULL;
+
+ALTER TABLE `dormant_senior_users`
+  MODIFY COLUMN `phone` varchar(128) NOT NULL,
+  ADD COLUMN `phone_hash` varchar(64) DEFAULT NULL AFTER `phone`,
+  DROP INDEX `phone_unique`,
+  ADD UNIQUE KEY `phone_unique` (`phone_hash`);
+
+-- The pending codes expire in minutes, so they are dropped rather than
+-- hashing the numbers they were sent to
+DELETE FROM `phone_verifications`;
+
+ALTER TABLE `phone_verifications`
+  DROP COLUMN `phone`,
+  ADD COLUMN `phone_hash` varchar(64) NOT NULL AFTER `purpose`;
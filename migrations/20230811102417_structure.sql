@@ -0,0 +1,18 @@
+-- Add migration script here
+--
+-- Adds the settings of the users.
+
+CREATE TABLE `user_settings` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `user_type` varchar(16) NOT NULL,
+  `user_id` int(10) unsigned NOT NULL,
+  `email_notification` tinyint(1) NOT NULL DEFAULT 1,
+  `sms_notification` tinyint(1) NOT NULL DEFAULT 1,
+  `push_notification` tinyint(1) NOT NULL DEFAULT 1,
+  `contact_channel` varchar(16) NOT NULL DEFAULT 'Email',
+  `language` varchar(8) NOT NULL DEFAULT 'ko',
+  `theme` varchar(16) NOT NULL DEFAULT 'System',
+  `updated_at` timestamp NOT NULL DEFAULT current_timestamp() ON UPDATE current_timestamp(),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `unique_index` (`user_type`,`user_id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
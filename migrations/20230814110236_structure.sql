@@ -0,0 +1,16 @@
+-- Add migration script here
+--
+-- Adds the seniors the normal users viewed lately.
+
+CREATE TABLE `recently_viewed_seniors` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `normal_user_id` int(10) unsigned NOT NULL,
+  `senior_user_id` int(10) unsigned NOT NULL,
+  `viewed_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `unique_index` (`normal_user_id`,`senior_user_id`),
+  KEY `fk_recently_viewed_seniors_senior_user_id` (`senior_user_id`),
+  KEY `viewed_at_index` (`normal_user_id`,`viewed_at`),
+  CONSTRAINT `fk_recently_viewed_seniors_normal_user_id` FOREIGN KEY (`normal_user_id`) REFERENCES `normal_users` (`id`) ON DELETE CASCADE,
+  CONSTRAINT `fk_recently_viewed_seniors_senior_user_id` FOREIGN KEY (`senior_user_id`) REFERENCES `senior_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
@@ -0,0 +1,33 @@
+-- Add migration script here
+--
+-- Moves the majors of the seniors into a taxonomy.
+
+CREATE TABLE `majors` (
+  `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+  `parent_id` int(10) unsigned DEFAULT NULL,
+  `name` varchar(32) NOT NULL,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `name_unique` (`name`),
+  KEY `fk_majors_parent_id` (`parent_id`),
+  CONSTRAINT `fk_majors_parent_id` FOREIGN KEY (`parent_id`) REFERENCES `majors` (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_general_ci;
+
+INSERT INTO `majors` VALUES (1,NULL,'개발'),(2,1,'백엔드'),(3,1,'프론트엔드'),(4,1,'모바일'),(5,1,'데브옵스'),(6,1,'게임'),(7,NULL,'데이터'),(8,7,'데이터 분석'),(9,7,'머신러닝'),(10,NULL,'디자인'),(11,10,'UI/UX 디자인'),(12,10,'그래픽 디자인'),(13,NULL,'기획'),(14,13,'서비스 기획'),(15,13,'프로덕트 매니지먼트'),(16,NULL,'마케팅'),(17,16,'퍼포먼스 마케팅'),(18,16,'브랜드 마케팅');
+
+-- The majors were free text before, so tidy them up and keep the ones
+-- outside the taxonomy as the majors of a category of their own
+UPDATE `senior_users` SET `major` = TRIM(`major`);
+UPDATE `senior_users` SET `major` = '기타' WHERE `major` = '';
+
+INSERT INTO `majors` (`parent_id`, `name`)
+SELECT NULL, '기타' FROM DUAL
+WHERE EXISTS (SELECT 1 FROM `senior_users` WHERE `major` NOT IN (SELECT `name` FROM `majors`));
+
+INSERT INTO `majors` (`parent_id`, `name`)
+SELECT DISTINCT (SELECT `id` FROM `majors` WHERE `name` = '기타'), `major`
+FROM `senior_users`
+WHERE `major` NOT IN (SELECT `name` FROM `majors`);
+
+ALTER TABLE `senior_users`
+  ADD KEY `fk_senior_users_major` (`major`),
+  ADD CONSTRAINT `fk_senior_users_major` FOREIGN KEY (`major`) REFERENCES `majors` (`name`) ON UPDATE CASCADE;
@@ -2,11 +2,13 @@
 
 use std::io;
 
-use jsonwebtoken::{DecodingKey, EncodingKey};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Validation};
 
 use crate::{
+    crypto::FieldCipher,
     env::get_env_or_panic,
     oauth::{OAuthConfig, OAuthProvider},
+    sms::SmsConfig,
 };
 
 #[derive(Clone)]
@@ -19,11 +21,31 @@ pub struct Config {
     pub kakao_oauth: OAuthConfig,
     pub naver_oauth: OAuthConfig,
 
+    pub sms: SmsConfig,
+
     pub private_key: RSAKey,
     pub public_key: RSAKey,
 
+    pub field_cipher: FieldCipher,
+
     pub access_token_max_age: i64,
     pub refresh_token_max_age: i64,
+
+    /// Expected `iss` claim of the JWTs we issue and accept
+    pub jwt_issuer: String,
+    /// Expected `aud` claim of the JWTs we issue and accept
+    pub jwt_audience: String,
+    /// Allowed clock skew in seconds when validating `exp` and `nbf`
+    pub jwt_leeway: u64,
+
+    /// Days to keep the deleted accounts before purging them permanently
+    pub account_deletion_grace_days: u32,
+    /// Days without login after which the accounts become dormant
+    pub dormant_account_inactive_days: u32,
+
+    /// Whether the server is behind a reverse proxy appending the address of
+    /// the client to `X-Forwarded-For`, whose last entry is then trusted
+    pub trust_forwarded_for: bool,
 }
 
 #[derive(Clone)]
@@ -73,6 +95,8 @@ impl Config {
             kakao_oauth: OAuthConfig::init(OAuthProvider::Kakao),
             naver_oauth: OAuthConfig::init(OAuthProvider::Naver),
 
+            sms: SmsConfig::init(),
+
             private_key: RSAKey::from_file(
                 &std::path::PathBuf::from(get_env_or_panic("RSA_PRIVATE_PEM_FILE_PATH"))
                     .to_path_buf(),
@@ -84,12 +108,38 @@ impl Config {
             )
             .expect("Cannot open the public key file"),
 
+            field_cipher: FieldCipher::init(),
+
             access_token_max_age: get_env_or_panic("ACCESS_TOKEN_MAX_AGE").parse::<i64>().unwrap(),
             refresh_token_max_age: get_env_or_panic("REFRESH_TOKEN_MAX_AGE")
                 .parse::<i64>()
                 .unwrap(),
+
+            jwt_issuer: get_env_or_panic("JWT_ISSUER"),
+            jwt_audience: get_env_or_panic("JWT_AUDIENCE"),
+            jwt_leeway: get_env_or_panic("JWT_LEEWAY").parse::<u64>().unwrap(),
+
+            account_deletion_grace_days: get_env_or_panic("ACCOUNT_DELETION_GRACE_DAYS")
+                .parse::<u32>()
+                .unwrap(),
+            dormant_account_inactive_days: get_env_or_panic("DORMANT_ACCOUNT_INACTIVE_DAYS")
+                .parse::<u32>()
+                .unwrap(),
+
+            trust_forwarded_for: get_env_or_panic("TRUST_FORWARDED_FOR").parse::<bool>().unwrap(),
         }
     }
+
+    /// Returns the validation rules used to decode the JWTs issued by us.
+    pub fn jwt_validation(&self) -> Validation {
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.leeway = self.jwt_leeway;
+        validation.set_issuer(&[&self.jwt_issuer]);
+        validation.set_audience(&[&self.jwt_audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        validation
+    }
 }
 
 impl Default for Config {
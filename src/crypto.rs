@@ -0,0 +1,169 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::{env::get_env_or_panic, error::ErrorResponse, Result};
+
+/// Encrypts the personal data stored in the database, so a dump of the
+/// database does not expose it.
+#[derive(Clone)]
+pub struct FieldCipher {
+    encryption_key: Vec<u8>,
+    hash_key: hmac::Key,
+}
+
+impl FieldCipher {
+    pub fn init() -> Self {
+        let decode_key = |env: &str| {
+            let key = STANDARD
+                .decode(get_env_or_panic(env))
+                .unwrap_or_else(|_| panic!("{env} must be encoded in base64"));
+            assert_eq!(key.len(), 32, "{env} must be 256 bits long");
+            key
+        };
+
+        Self::new(&decode_key("FIELD_ENCRYPTION_KEY"), &decode_key("FIELD_HASH_KEY"))
+    }
+
+    fn new(encryption_key: &[u8], hash_key: &[u8]) -> Self {
+        Self {
+            encryption_key: encryption_key.to_vec(),
+            hash_key: hmac::Key::new(hmac::HMAC_SHA256, hash_key),
+        }
+    }
+
+    fn key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.encryption_key).unwrap())
+    }
+
+    /// Encrypts the text with AES-256-GCM and a random nonce. The nonce is
+    /// stored in front of the ciphertext.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let encryption_error = || {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorResponse {
+                    message: "Failed to encrypt personal data".to_string(),
+                    code: "encryption_failed",
+                    invalid_fields: Vec::new(),
+                },
+            )
+        };
+
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new().fill(&mut nonce).map_err(|_| encryption_error())?;
+
+        let mut ciphertext = plaintext.as_bytes().to_vec();
+        self.key()
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut ciphertext,
+            )
+            .map_err(|_| encryption_error())?;
+
+        Ok(STANDARD.encode([nonce.as_slice(), &ciphertext].concat()))
+    }
+
+    /// Decrypts the text encrypted by [`FieldCipher::encrypt`].
+    pub fn decrypt(&self, encrypted: &str) -> Result<String> {
+        let decryption_error = || {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorResponse {
+                    message: "Failed to decrypt personal data".to_string(),
+                    code: "decryption_failed",
+                    invalid_fields: Vec::new(),
+                },
+            )
+        };
+
+        let data = STANDARD.decode(encrypted).map_err(|_| decryption_error())?;
+        if data.len() < NONCE_LEN {
+            return Err(decryption_error());
+        }
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let mut ciphertext = ciphertext.to_vec();
+        let plaintext = self
+            .key()
+            .open_in_place(
+                Nonce::try_assume_unique_for_key(nonce).map_err(|_| decryption_error())?,
+                Aad::empty(),
+                &mut ciphertext,
+            )
+            .map_err(|_| decryption_error())?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|_| decryption_error())
+    }
+
+    /// Returns a keyed hash of the text. The encrypted values are different
+    /// every time, so the hash is used to look up and compare them instead.
+    pub fn hash(&self, plaintext: &str) -> String {
+        URL_SAFE_NO_PAD.encode(hmac::sign(&self.hash_key, plaintext.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use super::FieldCipher;
+
+    fn cipher() -> FieldCipher {
+        FieldCipher::new(&[1; 32], &[2; 32])
+    }
+
+    #[test]
+    fn decrypts_encrypted_text() {
+        let cipher = cipher();
+        for plaintext in ["+821012345678", "", "전화번호"] {
+            let encrypted = cipher.encrypt(plaintext).unwrap();
+            assert_ne!(encrypted, plaintext);
+            assert_eq!(cipher.decrypt(&encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn encrypts_with_random_nonces() {
+        let cipher = cipher();
+        assert_ne!(
+            cipher.encrypt("+821012345678").unwrap(),
+            cipher.encrypt("+821012345678").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_or_foreign_text() {
+        let cipher = cipher();
+        let encrypted = cipher.encrypt("+821012345678").unwrap();
+
+        let mut tampered = STANDARD.decode(&encrypted).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(cipher.decrypt(&STANDARD.encode(tampered)).is_err());
+
+        assert!(FieldCipher::new(&[3; 32], &[2; 32]).decrypt(&encrypted).is_err());
+        assert!(cipher.decrypt("not base64").is_err());
+        assert!(cipher.decrypt(&STANDARD.encode([0u8; 4])).is_err());
+    }
+
+    #[test]
+    fn hashes_deterministically() {
+        let cipher = cipher();
+        assert_eq!(cipher.hash("+821012345678"), cipher.hash("+821012345678"));
+        assert_ne!(cipher.hash("+821012345678"), cipher.hash("+821012345679"));
+        assert_ne!(
+            cipher.hash("+821012345678"),
+            FieldCipher::new(&[1; 32], &[3; 32]).hash("+821012345678")
+        );
+    }
+}
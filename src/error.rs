@@ -1,18 +1,265 @@
 // Copyright 2023. The resback authors all rights reserved.
 
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use std::{any::Any, sync::Arc};
+
+use axum::{
+    extract::State,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::CookieJar;
 use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    i18n,
+    jwt::{self, Token},
+    user::settings::{Locale, UserSettings},
+    AppState,
+};
+
+/// MySQL error number of the duplicate entries for unique keys
+const ER_DUP_ENTRY: u16 = 1062;
+/// MySQL error number of deleting or updating a row referenced by another row
+const ER_ROW_IS_REFERENCED_2: u16 = 1451;
+/// MySQL error number of referring to a row which does not exist
+const ER_NO_REFERENCED_ROW_2: u16 = 1452;
+/// MySQL error number of giving up waiting for a lock
+const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+/// MySQL error number of a transaction rolled back to resolve a deadlock
+const ER_LOCK_DEADLOCK: u16 = 1213;
+
+/// Seconds the clients are asked to wait before retrying when the database is
+/// busy
+const DATABASE_RETRY_AFTER: u64 = 3;
+
+/// Header carrying the identifier of the request, which is reported back as
+/// the `instance` of the problem details
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
 
-#[derive(Debug, Serialize)]
+/// Base of the URIs identifying the problem types. The `code` of the error
+/// is appended to it.
+const PROBLEM_TYPE_BASE_URI: &str = "https://respec.team/problems/";
+
+const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+#[derive(Debug)]
 pub struct ErrorResponse {
-    pub status: &'static str,
+    /// Human-readable explanation of the error, sent as the `detail`
     pub message: String,
+    /// Stable identifier of the error for the clients to branch on, such as
+    /// `user_not_found`. Unlike the message, it never changes once published.
+    pub code: &'static str,
+    /// Fields of the request which failed the validation. They are reported
+    /// apart from the message so that they survive the localization.
+    pub invalid_fields: Vec<&'static str>,
 }
 
 impl IntoResponse for ErrorResponse {
-    fn into_response(self) -> axum::response::Response {
-        Json(self).into_response()
+    fn into_response(self) -> Response {
+        // The status code is not known here since it is paired with the error
+        // afterwards, so the body is written by `problem_details` instead.
+        let mut response = StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+/// Error response body following RFC 7807
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Identifier of the request which caused the problem
+    pub instance: String,
+    pub code: &'static str,
+    /// Fields of the request which failed the validation, if any
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid_fields: Vec<&'static str>,
+}
+
+impl ProblemDetails {
+    pub fn new(status: StatusCode, error: ErrorResponse, request_id: &str, locale: Locale) -> Self {
+        Self {
+            problem_type: format!("{}{}", PROBLEM_TYPE_BASE_URI, error.code),
+            title: status.canonical_reason().unwrap_or("Unknown Error"),
+            status: status.as_u16(),
+            detail: i18n::error_message(error.code, locale)
+                .map(str::to_string)
+                .unwrap_or(error.message),
+            instance: request_id.to_string(),
+            code: error.code,
+            invalid_fields: error.invalid_fields,
+        }
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, [(header::CONTENT_TYPE, PROBLEM_JSON_CONTENT_TYPE)], Json(self)).into_response()
+    }
+}
+
+/// Middleware tagging every request with an id and turning the errors returned
+/// by the handlers into `application/problem+json` responses.
+///
+/// The messages of the errors are written in the language requested by the
+/// `Accept-Language` header, or the one chosen by the user in the settings.
+pub async fn problem_details<B>(
+    cookies: CookieJar,
+    State(data): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:032x}", rand::random::<u128>()));
+    let requested_locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(i18n::negotiate_locale);
+    let access_token = jwt::find_access_token(&cookies, request.headers());
+
+    let mut response = next.run(request).await;
+    if let Some(error) = response.extensions_mut().remove::<ErrorResponse>() {
+        let locale = match requested_locale {
+            Some(locale) => locale,
+            None => preferred_locale(access_token.as_deref(), &data).await,
+        };
+
+        let mut problem_response =
+            ProblemDetails::new(response.status(), error, &request_id, locale).into_response();
+        // Keep the headers set alongside the error such as cookies
+        for (name, value) in response.headers() {
+            if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                problem_response.headers_mut().append(name, value.clone());
+            }
+        }
+        if problem_response.status() == StatusCode::SERVICE_UNAVAILABLE {
+            problem_response.headers_mut().insert(header::RETRY_AFTER, DATABASE_RETRY_AFTER.into());
+        }
+        problem_response.headers_mut().insert(
+            header::CONTENT_LANGUAGE,
+            HeaderValue::from_static(match locale {
+                Locale::Ko => "ko",
+                Locale::En => "en",
+            }),
+        );
+        response = problem_response;
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Turns a panic of a handler into an internal server error, so that the
+/// client gets a response instead of a dropped connection.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let reason = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown reason");
+    tracing::error!("Handler panicked: {}", reason);
+
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorResponse {
+            message: "An unexpected error occurred".to_string(),
+            code: "internal_error",
+            invalid_fields: Vec::new(),
+        },
+    )
+        .into_response()
+}
+
+/// Returns the language chosen by the user in the settings. The anonymous
+/// users get Korean.
+async fn preferred_locale(access_token: Option<&str>, data: &AppState) -> Locale {
+    match Token::from_encoded_token(access_token, &data.config) {
+        Ok(token) => UserSettings::locale((token.user_type(), token.user_id()), &data.database)
+            .await
+            .unwrap_or(Locale::Ko),
+        Err(_) => Locale::Ko,
     }
 }
 
 pub type Result<T> = std::result::Result<T, (StatusCode, ErrorResponse)>;
+
+/// Converts the error of a query to a response. The violations of the
+/// constraints are blamed on the request, and the contention of the locks is
+/// reported as a temporary unavailability.
+pub fn database_error(err: sqlx::Error) -> (StatusCode, ErrorResponse) {
+    if let sqlx::Error::PoolTimedOut = err {
+        return database_busy();
+    }
+
+    let number = err
+        .as_database_error()
+        .and_then(|err| err.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>())
+        .map(|err| err.number());
+    match number {
+        Some(ER_DUP_ENTRY) => (
+            StatusCode::CONFLICT,
+            ErrorResponse {
+                message: "The data conflicts with the existing one".to_string(),
+                code: "duplicate_entry",
+                invalid_fields: Vec::new(),
+            },
+        ),
+        Some(ER_ROW_IS_REFERENCED_2 | ER_NO_REFERENCED_ROW_2) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorResponse {
+                message: "The data refers to a missing one or is referred by another".to_string(),
+                code: "foreign_key_violation",
+                invalid_fields: Vec::new(),
+            },
+        ),
+        Some(ER_LOCK_WAIT_TIMEOUT | ER_LOCK_DEADLOCK) => database_busy(),
+        _ => {
+            // The error can tell the schema and the data, so it is only logged
+            tracing::error!("Database error: {}", err);
+
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorResponse {
+                    message: "Internal server error".to_string(),
+                    code: "database_error",
+                    invalid_fields: Vec::new(),
+                },
+            )
+        }
+    }
+}
+
+fn database_busy() -> (StatusCode, ErrorResponse) {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        ErrorResponse {
+            message: "The database is busy, try again later".to_string(),
+            code: "database_busy",
+            invalid_fields: Vec::new(),
+        },
+    )
+}
+
+/// Returns whether the error is caused by a duplicate entry for the unique key
+/// named `key`.
+pub fn is_duplicate_entry(err: &sqlx::Error, key: &str) -> bool {
+    err.as_database_error()
+        .and_then(|err| err.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>())
+        .is_some_and(|err| err.number() == ER_DUP_ENTRY && err.message().contains(key))
+}
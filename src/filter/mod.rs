@@ -0,0 +1,173 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+
+use crate::{error::ErrorResponse, Result};
+
+/// List of offensive words in Korean and English
+pub const BANNED_WORDS: &[&str] = &include!("banned_words.in");
+
+/// List of words that only the service can use in nicknames, to prevent users
+/// from impersonating the operators
+pub const RESERVED_WORDS: &[&str] = &include!("reserved_words.in");
+
+/// List of words that contain a banned or reserved word but are harmless,
+/// such as "시바견"
+pub const ALLOWED_WORDS: &[&str] = &include!("allowed_words.in");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Hangul,
+    Digit,
+    Other,
+}
+
+impl Script {
+    fn of(c: char) -> Self {
+        match c {
+            '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' | '\u{ac00}'..='\u{d7a3}' => {
+                Self::Hangul
+            }
+            _ if c.is_numeric() => Self::Digit,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Splits the lowercased text into the runs of letters or digits of the same
+/// script, so that "admin123" or "관리자admin" are split into their words.
+fn tokenize(text: &str) -> Vec<(Script, String)> {
+    let mut tokens: Vec<(Script, String)> = Vec::new();
+    let mut last_is_separator = true;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if !c.is_alphanumeric() {
+            last_is_separator = true;
+            continue;
+        }
+
+        let script = Script::of(c);
+        match tokens.last_mut() {
+            Some((last_script, token)) if !last_is_separator && *last_script == script => {
+                token.push(c)
+            }
+            _ => tokens.push((script, c.to_string())),
+        }
+        last_is_separator = false;
+    }
+    tokens
+}
+
+/// Returns whether the text contains any of the words. The Korean words are
+/// searched inside the words of the text since they are followed by particles
+/// and suffixes, after taking out the allowed compounds. The other words have
+/// to match a whole word, so that "Dickens" is not taken for "dick".
+fn contains_any(text: &str, words: &[&str]) -> bool {
+    tokenize(text).into_iter().any(|(script, token)| match script {
+        Script::Hangul => {
+            let token =
+                ALLOWED_WORDS.iter().fold(token, |token, allowed| token.replace(allowed, " "));
+            words.iter().any(|word| token.contains(word))
+        }
+        Script::Digit | Script::Other => words.contains(&token.as_str()),
+    })
+}
+
+/// Returns whether the text contains an offensive word.
+pub fn contains_banned_word(text: &str) -> bool {
+    contains_any(text, BANNED_WORDS)
+}
+
+/// Returns whether the text contains a word reserved for the service.
+pub fn contains_reserved_word(text: &str) -> bool {
+    contains_any(text, RESERVED_WORDS)
+}
+
+/// Returns an error if the nickname contains an offensive or reserved word.
+pub fn check_nickname(nickname: &str) -> Result<()> {
+    if contains_banned_word(nickname) || contains_reserved_word(nickname) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Nickname contains a word that is not allowed".to_string(),
+                code: "banned_word",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns an error if the profile tag contains an offensive or reserved word.
+pub fn check_tag(tag: &str) -> Result<()> {
+    if contains_banned_word(tag) || contains_reserved_word(tag) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Tag contains a word that is not allowed".to_string(),
+                code: "banned_word",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns an error if the profile description contains an offensive word.
+pub fn check_description(description: &str) -> Result<()> {
+    if contains_banned_word(description) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Description contains a word that is not allowed".to_string(),
+                code: "banned_word",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contains_banned_word, contains_reserved_word};
+
+    #[test]
+    fn finds_banned_words() {
+        assert!(contains_banned_word("시발"));
+        assert!(contains_banned_word("개새끼야"));
+        assert!(contains_banned_word("이 병신아"));
+        assert!(contains_banned_word("ㅅㅂ"));
+        assert!(contains_banned_word("What the FUCK"));
+        assert!(contains_banned_word("dick123"));
+        assert!(contains_banned_word("시바견 시바"));
+    }
+
+    #[test]
+    fn allows_harmless_words() {
+        assert!(!contains_banned_word("우리 집 시바견"));
+        assert!(!contains_banned_word("새끼고양이를 키워요"));
+        assert!(!contains_banned_word("I love Dickens"));
+        assert!(!contains_banned_word("Shitake mushrooms"));
+        assert!(!contains_banned_word("Class of 2023"));
+    }
+
+    #[test]
+    fn finds_reserved_words() {
+        assert!(contains_reserved_word("관리자"));
+        assert!(contains_reserved_word("리스펙운영자"));
+        assert!(contains_reserved_word("Admin_Kim"));
+        assert!(contains_reserved_word("관리자admin"));
+        assert!(contains_reserved_word("official 계정"));
+    }
+
+    #[test]
+    fn allows_words_containing_reserved_words() {
+        assert!(!contains_reserved_word("unofficial"));
+        assert!(!contains_reserved_word("ecosystem"));
+        assert!(!contains_reserved_word("리스펙트"));
+        assert!(!contains_reserved_word("수학공식"));
+    }
+}
@@ -6,10 +6,9 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension,
 };
 use axum_extra::extract::{cookie::Cookie, CookieJar};
-use axum_typed_multipart::TypedMultipart;
 use oauth2::{
     reqwest::async_http_client, AuthorizationCode, ErrorResponse, RevocableToken,
     TokenIntrospectionResponse, TokenResponse, TokenType,
@@ -20,8 +19,14 @@ use crate::{
     error,
     jwt::Token,
     oauth::{GoogleUser, KakaoUser, NaverUserResponse, OAuthProvider},
-    schema::{NormalLoginSchema, SeniorLoginSchema, UserIdentificationSchema},
-    user::account::{SeniorUser, UserId},
+    schema::{
+        self, AccountRecoveryCompleteSchema, AccountRecoveryRequestSchema, JsonOrMultipart,
+        NormalLoginSchema, SeniorLoginSchema, SuccessResponse, UserIdentificationSchema,
+    },
+    user::{
+        account::{SeniorUser, UserId},
+        recovery::AccountRecovery,
+    },
     AppState,
 };
 use crate::{
@@ -39,11 +44,22 @@ pub struct AuthRequest {
     state: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/{provider}",
+    tag = "auth",
+    params(("provider" = OAuthProvider, Path, description = "OAuth provider the code is issued by")),
+    request_body(content = NormalLoginSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
 pub async fn auth_provider(
     cookie_jar: CookieJar,
     Path(provider): Path<OAuthProvider>,
     State(data): State<Arc<AppState>>,
-    TypedMultipart(login_data): TypedMultipart<NormalLoginSchema>,
+    JsonOrMultipart(login_data): JsonOrMultipart<NormalLoginSchema>,
 ) -> impl IntoResponse {
     let oauth_id: String;
 
@@ -90,56 +106,91 @@ pub async fn auth_provider(
     add_token_pair_to_cookie_jar(&user, UserType::NormalUser, cookie_jar, &data).await
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/senior",
+    tag = "auth",
+    request_body(content = SeniorLoginSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
 pub async fn auth_senior(
     cookie_jar: CookieJar,
     State(data): State<Arc<AppState>>,
-    TypedMultipart(login_data): TypedMultipart<SeniorLoginSchema>,
+    JsonOrMultipart(login_data): JsonOrMultipart<SeniorLoginSchema>,
 ) -> crate::Result<impl IntoResponse> {
+    schema::validate(&login_data)?;
     let user = SeniorUser::login(&login_data.email, &login_data.password, &data.database).await?;
 
     add_token_pair_to_cookie_jar(&user, UserType::SeniorUser, cookie_jar, &data).await
 }
 
+#[utoipa::path(
+    patch,
+    path = "/auth/token",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
 pub async fn auth_refresh(
     cookie_jar: CookieJar,
     State(data): State<Arc<AppState>>,
 ) -> crate::Result<impl IntoResponse> {
     let refresh_token = cookie_jar.get(REFRESH_TOKEN_COOKIE).map(|token| token.value().to_string());
 
-    let (user_id, user_type) =
-        Token::from_encoded_token(refresh_token.as_deref(), data.config.public_key.decoding_key())
-            .map(|token| (token.user_id(), token.user_type()))?;
+    let (user_id, user_type) = Token::from_encoded_token(refresh_token.as_deref(), &data.config)
+        .map(|token| (token.user_id(), token.user_type()))?;
     let refresh_token = refresh_token.unwrap();
 
-    let user_token = match user_type {
+    let (user_token, token_version) = match user_type {
         UserType::NormalUser => {
             let user = NormalUser::from_id(user_id, &data.database).await?;
-            user.refresh_token().map(str::to_string)
+            (user.refresh_token().map(str::to_string), user.token_version())
         }
         UserType::SeniorUser => {
             let user = SeniorUser::from_id(user_id, &data.database).await?;
-            user.refresh_token().map(str::to_string)
+            (user.refresh_token().map(str::to_string), user.token_version())
         }
     };
 
     let user_token = user_token.ok_or((
         StatusCode::UNAUTHORIZED,
-        error::ErrorResponse { status: "fail", message: "You are not logged in".to_string() },
+        error::ErrorResponse {
+            message: "You are not logged in".to_string(),
+            code: "not_logged_in",
+            invalid_fields: Vec::new(),
+        },
     ))?;
 
     if refresh_token != user_token {
         return Err((
             StatusCode::UNAUTHORIZED,
             error::ErrorResponse {
-                status: "fail",
                 message: "Authorization data and user data do not match".to_string(),
+                code: "token_mismatch",
+                invalid_fields: Vec::new(),
             },
         ));
     }
 
-    add_access_token_to_cookie_jar(user_id, user_type, cookie_jar, &data).await
+    add_access_token_to_cookie_jar(user_id, user_type, token_version, cookie_jar, &data).await
 }
 
+#[utoipa::path(
+    delete,
+    path = "/auth/token",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
 pub async fn logout_user(
     cookie_jar: CookieJar,
     State(data): State<Arc<AppState>>,
@@ -147,41 +198,114 @@ pub async fn logout_user(
     let access_token = cookie_jar.get(ACCESS_TOKEN_COOKIE).ok_or((
         StatusCode::INTERNAL_SERVER_ERROR,
         (crate::error::ErrorResponse {
-            status: "error",
             message: "Failed to get login information".to_string(),
+            code: "not_logged_in",
+            invalid_fields: Vec::new(),
         }),
     ))?;
     let _refresh_token = cookie_jar.get(REFRESH_TOKEN_COOKIE).ok_or((
         StatusCode::INTERNAL_SERVER_ERROR,
         (crate::error::ErrorResponse {
-            status: "error",
             message: "Failed to get login information".to_string(),
+            code: "not_logged_in",
+            invalid_fields: Vec::new(),
         }),
     ))?;
 
-    let (user_type, id) = Token::from_encoded_token(
-        Some(access_token.value()),
-        data.config.public_key.decoding_key(),
-    )
-    .map(|token| (token.user_type(), token.user_id()))
-    .map_err(|_| {
-        (
-            StatusCode::UNAUTHORIZED,
-            crate::error::ErrorResponse {
-                status: "fail",
-                message: "Failed to verify user".to_string(),
-            },
-        )
-    })?;
+    let (user_type, id) = Token::from_encoded_token(Some(access_token.value()), &data.config)
+        .map(|token| (token.user_type(), token.user_id()))
+        .map_err(|_| {
+            (
+                StatusCode::UNAUTHORIZED,
+                crate::error::ErrorResponse {
+                    message: "Failed to verify user".to_string(),
+                    code: "invalid_token",
+                    invalid_fields: Vec::new(),
+                },
+            )
+        })?;
+
+    let access_token = Cookie::build(ACCESS_TOKEN_COOKIE, "").path("/").finish();
+    let refresh_token = Cookie::build(REFRESH_TOKEN_COOKIE, "").path("/").finish();
+    Ok((
+        cookie_jar.remove(access_token).remove(refresh_token),
+        SuccessResponse::new(UserIdentificationSchema { user_type, id }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn logout_all_sessions(
+    cookie_jar: CookieJar,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    let (user_type, id) = (token.user_type(), token.user_id());
+
+    match user_type {
+        UserType::NormalUser => NormalUser::revoke_sessions(id, &data.database).await?,
+        UserType::SeniorUser => SeniorUser::revoke_sessions(id, &data.database).await?,
+    };
 
     let access_token = Cookie::build(ACCESS_TOKEN_COOKIE, "").path("/").finish();
     let refresh_token = Cookie::build(REFRESH_TOKEN_COOKIE, "").path("/").finish();
     Ok((
         cookie_jar.remove(access_token).remove(refresh_token),
-        Json(UserIdentificationSchema { user_type, id }),
+        SuccessResponse::new(UserIdentificationSchema { user_type, id }),
     ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/senior/recovery",
+    tag = "auth",
+    request_body(content = AccountRecoveryRequestSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 202, description = "Verification code is sent to the phone of the senior"),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn request_senior_recovery(
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(recovery_data): JsonOrMultipart<AccountRecoveryRequestSchema>,
+) -> crate::Result<impl IntoResponse> {
+    AccountRecovery::request(
+        &recovery_data,
+        &data.config.field_cipher,
+        &data.sms_client,
+        &data.database,
+    )
+    .await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/senior/recovery/confirm",
+    tag = "auth",
+    request_body(content = AccountRecoveryCompleteSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn complete_senior_recovery(
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(recovery_data): JsonOrMultipart<AccountRecoveryCompleteSchema>,
+) -> crate::Result<impl IntoResponse> {
+    AccountRecovery::complete(&recovery_data, &data.database).await.map(|id| {
+        SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id })
+    })
+}
+
 async fn get_oauth_user_data<U, TE, TR, TT, TIR, RT, TRE>(
     oauth_client: &oauth2::Client<TE, TR, TT, TIR, RT, TRE>,
     user_data_url: &str,
@@ -218,14 +342,16 @@ where
 async fn add_access_token_to_cookie_jar(
     user_id: UserId,
     user_type: UserType,
+    token_version: u32,
     cookie_jar: CookieJar,
     data: &AppState,
 ) -> crate::Result<(CookieJar, impl IntoResponse)> {
     let access_token = Token::new(
-        data.config.private_key.encoding_key(),
+        &data.config,
         chrono::Duration::seconds(data.config.access_token_max_age),
         user_type,
         user_id,
+        token_version,
     )?;
 
     Ok((
@@ -236,7 +362,7 @@ async fn add_access_token_to_cookie_jar(
                 .max_age(time::Duration::seconds(access_token.claims().expires_in()))
                 .finish(),
         ),
-        Json(UserIdentificationSchema { user_type, id: user_id }),
+        SuccessResponse::new(UserIdentificationSchema { user_type, id: user_id }),
     ))
 }
 
@@ -249,14 +375,21 @@ async fn add_token_pair_to_cookie_jar<U>(
 where
     U: User,
 {
-    let (cookie_jar, _response) =
-        add_access_token_to_cookie_jar(user.id(), user_type, cookie_jar, data).await?;
+    let (cookie_jar, _response) = add_access_token_to_cookie_jar(
+        user.id(),
+        user_type,
+        user.token_version(),
+        cookie_jar,
+        data,
+    )
+    .await?;
 
     let refresh_token = Token::new(
-        data.config.private_key.encoding_key(),
+        &data.config,
         chrono::Duration::seconds(data.config.refresh_token_max_age),
         user_type,
         user.id(),
+        user.token_version(),
     )?;
 
     user.update_refresh_token(refresh_token.encoded_token(), &data.database).await?;
@@ -269,6 +402,6 @@ where
                 .max_age(time::Duration::seconds(refresh_token.claims().expires_in()))
                 .finish(),
         ),
-        Json(UserIdentificationSchema { user_type, id: user.id() }),
+        SuccessResponse::new(UserIdentificationSchema { user_type, id: user.id() }),
     ))
 }
@@ -0,0 +1,20 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse};
+
+use crate::{major::Major, schema::SuccessResponse, AppState};
+
+#[utoipa::path(
+    get,
+    path = "/majors",
+    tag = "majors",
+    responses(
+        (status = 200, description = "Majors grouped by their categories", body = crate::schema::MajorCategoryListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_majors(State(data): State<Arc<AppState>>) -> crate::Result<impl IntoResponse> {
+    Ok(SuccessResponse::new(Major::get_all(&data.database).await?))
+}
@@ -1,7 +1,9 @@
 // Copyright 2023. The resback authors all rights reserved.
 
 pub mod auth;
+pub mod majors;
 pub mod root;
+pub mod tags;
 pub mod users;
 
 pub use root::root;
@@ -2,6 +2,15 @@
 
 use crate::about;
 
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "root",
+    responses(
+        (status = 200, description = "Name and version of the service", body = String, content_type = "text/plain"),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
 pub async fn root() -> String {
     about()
 }
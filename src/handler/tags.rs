@@ -0,0 +1,22 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse};
+
+use crate::{schema::SuccessResponse, tag::Tag, AppState};
+
+#[utoipa::path(
+    get,
+    path = "/tags/popular",
+    tag = "tags",
+    responses(
+        (status = 200, description = "Most used tags", body = crate::schema::TagListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_popular_tags(
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    Ok(SuccessResponse::new(Tag::get_popular(&data.database).await?))
+}
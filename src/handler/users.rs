@@ -2,69 +2,853 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension,
 };
 
-use axum_typed_multipart::TypedMultipart;
-
 use crate::{
+    error::ErrorResponse,
+    jwt::Token,
     schema::{
-        NormalUserInfoSchema, SeniorRegisterSchema, SeniorSearchSchema, SeniorUserInfoSchema,
-        UserIdentificationSchema,
+        ConsentAnswerSchema, CursorParams, JsonOrMultipart, NicknameAvailabilityQuery,
+        NicknameAvailabilitySchema, NormalUpdateSchema, NormalUserInfoSchema,
+        PhoneVerificationSchema, SeniorRegisterSchema, SeniorSearchSchema, SeniorTagsSchema,
+        SeniorUpdateSchema, SeniorUserInfoSchema, SuccessResponse, SuggestionQuery,
+        UserDataExportSchema, UserIdentificationSchema, UserNicknameSchema,
+        UserProfileExportSchema, UserReportSchema, UserSettingsUpdateSchema,
     },
+    suggestion::Suggestion,
+    tag::Tag,
     user::{
         account::{NormalUser, SeniorUser, User, UserId},
+        block::{Block, Report},
+        consent::Consent,
+        favorite::Favorite,
+        nickname,
+        phone::{PhoneVerification, VerificationPurpose},
+        recently_viewed::RecentlyViewed,
+        settings::UserSettings,
         UserType,
     },
     AppState, Result,
 };
 
+#[utoipa::path(
+    post,
+    path = "/users/senior",
+    tag = "users",
+    request_body(content = SeniorRegisterSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
 pub async fn register_senior_user(
     State(data): State<Arc<AppState>>,
-    TypedMultipart(register_data): TypedMultipart<SeniorRegisterSchema>,
+    JsonOrMultipart(register_data): JsonOrMultipart<SeniorRegisterSchema>,
 ) -> Result<impl IntoResponse> {
-    let id = SeniorUser::register(&register_data, &data.database).await?;
-    Ok(Json(UserIdentificationSchema { user_type: UserType::SeniorUser, id }))
+    let id =
+        SeniorUser::register(&register_data, &data.config.field_cipher, &data.database).await?;
+    Ok(SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/senior/{id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Profile of the senior", body = crate::schema::SeniorUserInfoResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security((), ("access_token" = []), ("bearer" = []))
+)]
 pub async fn get_senior_user_info(
     Path(id): Path<UserId>,
+    token: Option<Extension<Token>>,
     State(data): State<Arc<AppState>>,
 ) -> crate::Result<impl IntoResponse> {
     let user = SeniorUser::from_id(id, &data.database).await?;
-    Ok(Json(SeniorUserInfoSchema::from(user)))
+
+    // The seniors looking at their own profile are not counted
+    let is_owner = token.as_ref().is_some_and(|Extension(token)| {
+        token.user_type() == UserType::SeniorUser && token.user_id() == id
+    });
+    if !is_owner {
+        SeniorUser::record_view(id, &data.database).await?;
+    }
+
+    let mut user_info = SeniorUserInfoSchema::from(user);
+
+    if let Some(Extension(token)) = token {
+        if token.user_type() == UserType::NormalUser {
+            RecentlyViewed::record(token.user_id(), id, &data.database).await?;
+            Favorite::mark_seniors(
+                token.user_id(),
+                std::slice::from_mut(&mut user_info),
+                &data.database,
+            )
+            .await?;
+        }
+    }
+
+    Ok(SuccessResponse::new(user_info))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/users/senior/{id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
 pub async fn delete_senior_user(
     Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
     State(data): State<Arc<AppState>>,
 ) -> crate::Result<impl IntoResponse> {
-    SeniorUser::delete(id, &data.database)
-        .await
-        .map(|id| Json(UserIdentificationSchema { user_type: UserType::SeniorUser, id }))
+    token.verify_owner(UserType::SeniorUser, id)?;
+
+    SeniorUser::delete(id, &data.database).await.map(|id| {
+        SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id })
+    })
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/normal/{id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    responses(
+        (status = 200, description = "Profile of the normal user", body = crate::schema::NormalUserInfoResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
 pub async fn get_normal_user_info(
     Path(id): Path<UserId>,
     State(data): State<Arc<AppState>>,
 ) -> crate::Result<impl IntoResponse> {
     let user = NormalUser::from_id(id, &data.database).await?;
-    Ok(Json(NormalUserInfoSchema::from(user)))
+    Ok(SuccessResponse::new(NormalUserInfoSchema::from(user)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/users/normal/{id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
 pub async fn delete_normal_user(
     Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
     State(data): State<Arc<AppState>>,
 ) -> crate::Result<impl IntoResponse> {
-    NormalUser::delete(id, &data.database)
-        .await
-        .map(|id| Json(UserIdentificationSchema { user_type: UserType::NormalUser, id }))
+    token.verify_owner(UserType::NormalUser, id)?;
+
+    NormalUser::delete(id, &data.database).await.map(|id| {
+        SuccessResponse::new(UserIdentificationSchema { user_type: UserType::NormalUser, id })
+    })
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/senior",
+    tag = "users",
+    params(SeniorSearchSchema, CursorParams),
+    responses(
+        (status = 200, description = "Page of the seniors", body = crate::schema::SeniorUserInfoListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security((), ("access_token" = []), ("bearer" = []))
+)]
 pub async fn get_seniors(
     Query(search_info): Query<SeniorSearchSchema>,
+    page: CursorParams,
+    token: Option<Extension<Token>>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    let viewer = token.as_ref().map(|Extension(token)| (token.user_type(), token.user_id()));
+    let mut seniors = SeniorUser::get_all(search_info, page, viewer, &data.database).await?;
+
+    if let Some(Extension(token)) = token {
+        if token.user_type() == UserType::NormalUser {
+            Favorite::mark_seniors(token.user_id(), &mut seniors.items, &data.database).await?;
+        }
+    }
+
+    Ok(SuccessResponse::from(seniors))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/senior/suggest",
+    tag = "users",
+    params(SuggestionQuery),
+    responses(
+        (status = 200, description = "Suggested search words", body = crate::schema::SuggestionListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn suggest_seniors(
+    Query(query): Query<SuggestionQuery>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    Ok(SuccessResponse::new(Suggestion::get(&query.q, &data.database).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/senior/{id}/tags",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Tags of the senior", body = crate::schema::StringListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn get_senior_tags(
+    Path(id): Path<UserId>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    // Make sure the senior exists before listing the tags
+    SeniorUser::from_id(id, &data.database).await?;
+
+    Ok(SuccessResponse::new(Tag::get_senior_tags(id, &data.database).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/senior/{id}/tags",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    request_body(content = SeniorTagsSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "New tags of the senior", body = crate::schema::StringListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn update_senior_tags(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(tags_data): JsonOrMultipart<SeniorTagsSchema>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::SeniorUser, id)?;
+
+    Ok(SuccessResponse::new(Tag::set_senior_tags(id, &tags_data.tags.0, &data.database).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/senior/recommended",
+    tag = "users",
+    responses(
+        (status = 200, description = "Seniors recommended to the normal user", body = crate::schema::SeniorUserInfoListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn get_recommended_seniors(
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    if token.user_type() != UserType::NormalUser {
+        return Err((
+            StatusCode::FORBIDDEN,
+            ErrorResponse {
+                message: "Only normal users can get recommendations".to_string(),
+                code: "normal_user_only",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    Ok(SuccessResponse::new(SeniorUser::get_recommended(token.user_id(), &data.database).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/recently-viewed",
+    tag = "users",
+    responses(
+        (status = 200, description = "Seniors the normal user viewed lately", body = crate::schema::SeniorUserInfoListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn get_recently_viewed_seniors(
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    if token.user_type() != UserType::NormalUser {
+        return Err((
+            StatusCode::FORBIDDEN,
+            ErrorResponse {
+                message: "Only normal users have recently viewed seniors".to_string(),
+                code: "normal_user_only",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    let mut seniors = RecentlyViewed::get_all(token.user_id(), &data.database).await?;
+    Favorite::mark_seniors(token.user_id(), &mut seniors, &data.database).await?;
+
+    Ok(SuccessResponse::new(seniors))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/normal/{id}/favorites",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user"), CursorParams),
+    responses(
+        (status = 200, description = "Page of the favorite seniors", body = crate::schema::SeniorUserInfoListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn get_favorite_seniors(
+    Path(id): Path<UserId>,
+    page: CursorParams,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::NormalUser, id)?;
+
+    Ok(SuccessResponse::from(Favorite::get_all(id, page, &data.database).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/normal/{id}/favorites/{senior_id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user"), ("senior_id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Identification of the senior", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn add_favorite_senior(
+    Path((id, senior_id)): Path<(UserId, UserId)>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::NormalUser, id)?;
+
+    // Make sure the senior exists before saving
+    SeniorUser::from_id(senior_id, &data.database).await?;
+
+    Favorite::add(id, senior_id, &data.database).await.map(|id| {
+        SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id })
+    })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/normal/{id}/favorites/{senior_id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user"), ("senior_id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Identification of the senior", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn remove_favorite_senior(
+    Path((id, senior_id)): Path<(UserId, UserId)>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::NormalUser, id)?;
+
+    Favorite::remove(id, senior_id, &data.database).await.map(|id| {
+        SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id })
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/senior/{id}/block",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn block_senior_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    SeniorUser::from_id(id, &data.database).await?;
+
+    Block::add((token.user_type(), token.user_id()), (UserType::SeniorUser, id), &data.database)
+        .await
+        .map(|id| {
+            SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id })
+        })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/senior/{id}/block",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn unblock_senior_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    Block::remove((token.user_type(), token.user_id()), (UserType::SeniorUser, id), &data.database)
+        .await
+        .map(|id| {
+            SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id })
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/senior/{id}/report",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    request_body(content = UserReportSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn report_senior_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(report_data): JsonOrMultipart<UserReportSchema>,
+) -> crate::Result<impl IntoResponse> {
+    SeniorUser::from_id(id, &data.database).await?;
+
+    Report::create(
+        (token.user_type(), token.user_id()),
+        (UserType::SeniorUser, id),
+        &report_data.reason,
+        &data.database,
+    )
+    .await?;
+
+    Ok(SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/normal/{id}/block",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn block_normal_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    NormalUser::from_id(id, &data.database).await?;
+
+    Block::add((token.user_type(), token.user_id()), (UserType::NormalUser, id), &data.database)
+        .await
+        .map(|id| {
+            SuccessResponse::new(UserIdentificationSchema { user_type: UserType::NormalUser, id })
+        })
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/normal/{id}/block",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn unblock_normal_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    Block::remove((token.user_type(), token.user_id()), (UserType::NormalUser, id), &data.database)
+        .await
+        .map(|id| {
+            SuccessResponse::new(UserIdentificationSchema { user_type: UserType::NormalUser, id })
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/normal/{id}/report",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    request_body(content = UserReportSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn report_normal_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(report_data): JsonOrMultipart<UserReportSchema>,
+) -> crate::Result<impl IntoResponse> {
+    NormalUser::from_id(id, &data.database).await?;
+
+    Report::create(
+        (token.user_type(), token.user_id()),
+        (UserType::NormalUser, id),
+        &report_data.reason,
+        &data.database,
+    )
+    .await?;
+
+    Ok(SuccessResponse::new(UserIdentificationSchema { user_type: UserType::NormalUser, id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/export",
+    tag = "users",
+    responses(
+        (status = 200, description = "Every personal data of the user", body = crate::schema::UserDataExportResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn export_user_data(
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    let (user_type, id) = (token.user_type(), token.user_id());
+
+    let (profile, favorites, recently_viewed) = match user_type {
+        UserType::NormalUser => (
+            UserProfileExportSchema::Normal(NormalUser::from_id(id, &data.database).await?.into()),
+            Favorite::export(id, &data.database).await?,
+            RecentlyViewed::export(id, &data.database).await?,
+        ),
+        UserType::SeniorUser => {
+            let user = SeniorUser::from_id(id, &data.database).await?;
+            (
+                UserProfileExportSchema::Senior(user.export(&data.config.field_cipher)?),
+                Vec::new(),
+                Vec::new(),
+            )
+        }
+    };
+
+    Ok(SuccessResponse::new(UserDataExportSchema {
+        user_type,
+        profile,
+        favorites,
+        recently_viewed,
+        blocked_users: Block::export((user_type, id), &data.database).await?,
+        reports: Report::export((user_type, id), &data.database).await?,
+        consents: Consent::export((user_type, id), &data.database).await?,
+        settings: UserSettings::get((user_type, id), &data.database).await?,
+        exported_at: chrono::Utc::now(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/nickname/availability",
+    tag = "users",
+    params(NicknameAvailabilityQuery),
+    responses(
+        (status = 200, description = "Availability of the nickname", body = crate::schema::NicknameAvailabilityResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+pub async fn check_nickname_availability(
+    Query(query): Query<NicknameAvailabilityQuery>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    nickname::validate(&query.name)?;
+
+    let available = nickname::is_available(&query.name, &data.database).await?;
+    Ok(SuccessResponse::new(NicknameAvailabilitySchema { nickname: query.name, available }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/senior/{id}/nickname/regenerate",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "New nickname of the user", body = crate::schema::UserNicknameResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn regenerate_senior_user_nickname(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::SeniorUser, id)?;
+
+    let nickname = nickname::regenerate::<SeniorUser>(id, &data.database).await?;
+    Ok(SuccessResponse::new(UserNicknameSchema { user_type: UserType::SeniorUser, id, nickname }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/normal/{id}/nickname/regenerate",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    responses(
+        (status = 200, description = "New nickname of the user", body = crate::schema::UserNicknameResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn regenerate_normal_user_nickname(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::NormalUser, id)?;
+
+    let nickname = nickname::regenerate::<NormalUser>(id, &data.database).await?;
+    Ok(SuccessResponse::new(UserNicknameSchema { user_type: UserType::NormalUser, id, nickname }))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/users/senior/{id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    request_body(content = SeniorUpdateSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Updated profile of the senior", body = crate::schema::SeniorUserInfoResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn update_senior_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(update_data): JsonOrMultipart<SeniorUpdateSchema>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::SeniorUser, id)?;
+
+    SeniorUser::update(id, &update_data, &data.config.field_cipher, &data.database).await?;
+
+    let user = SeniorUser::from_id(id, &data.database).await?;
+    Ok(SuccessResponse::new(SeniorUserInfoSchema::from(user)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/users/normal/{id}",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the normal user")),
+    request_body(content = NormalUpdateSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Updated profile of the normal user", body = crate::schema::NormalUserInfoResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn update_normal_user(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(update_data): JsonOrMultipart<NormalUpdateSchema>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::NormalUser, id)?;
+
+    NormalUser::update(id, &update_data, &data.database).await?;
+
+    let user = NormalUser::from_id(id, &data.database).await?;
+    Ok(SuccessResponse::new(NormalUserInfoSchema::from(user)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/senior/{id}/phone/verification",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn send_phone_verification(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::SeniorUser, id)?;
+
+    let user = SeniorUser::from_id(id, &data.database).await?;
+    if user.phone_verified() {
+        return Err((
+            StatusCode::CONFLICT,
+            ErrorResponse {
+                message: "Phone number is already verified".to_string(),
+                code: "phone_already_verified",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    PhoneVerification::send(
+        id,
+        VerificationPurpose::PhoneVerification,
+        &user.phone(&data.config.field_cipher)?,
+        &data.config.field_cipher,
+        &data.sms_client,
+        &data.database,
+    )
+    .await?;
+    Ok(SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/senior/{id}/phone/verification/confirm",
+    tag = "users",
+    params(("id" = u64, Path, description = "Id of the senior user")),
+    request_body(content = PhoneVerificationSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn confirm_phone_verification(
+    Path(id): Path<UserId>,
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(verification_data): JsonOrMultipart<PhoneVerificationSchema>,
+) -> crate::Result<impl IntoResponse> {
+    token.verify_owner(UserType::SeniorUser, id)?;
+
+    PhoneVerification::confirm(id, &verification_data.code, &data.database).await?;
+    Ok(SuccessResponse::new(UserIdentificationSchema { user_type: UserType::SeniorUser, id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/consents",
+    tag = "users",
+    responses(
+        (status = 200, description = "Documents the user has to answer", body = crate::schema::PendingConsentListResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn get_pending_consents(
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    let consents =
+        Consent::get_pending((token.user_type(), token.user_id()), &data.database).await?;
+    Ok(SuccessResponse::new(consents))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/me/consents",
+    tag = "users",
+    request_body(content = ConsentAnswerSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "Identification of the user", body = crate::schema::UserIdentificationResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn answer_consent(
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+    JsonOrMultipart(consent_data): JsonOrMultipart<ConsentAnswerSchema>,
+) -> crate::Result<impl IntoResponse> {
+    let (user_type, id) = (token.user_type(), token.user_id());
+
+    Consent::answer(
+        (user_type, id),
+        consent_data.consent_type,
+        consent_data.version,
+        consent_data.agreed,
+        &data.database,
+    )
+    .await?;
+
+    Ok(SuccessResponse::new(UserIdentificationSchema { user_type, id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/settings",
+    tag = "users",
+    responses(
+        (status = 200, description = "Settings of the user", body = crate::schema::UserSettingsResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn get_user_settings(
+    Extension(token): Extension<Token>,
+    State(data): State<Arc<AppState>>,
+) -> crate::Result<impl IntoResponse> {
+    let settings = UserSettings::get((token.user_type(), token.user_id()), &data.database).await?;
+    Ok(SuccessResponse::new(settings))
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/me/settings",
+    tag = "users",
+    request_body(content = UserSettingsUpdateSchema, description = "Also accepted as `multipart/form-data`"),
+    responses(
+        (status = 200, description = "New settings of the user", body = crate::schema::UserSettingsResponse),
+        (status = "default", description = "Problem details of the error", body = crate::error::ProblemDetails, content_type = "application/problem+json")
+    ),
+    security(("access_token" = []), ("bearer" = []))
+)]
+pub async fn update_user_settings(
+    Extension(token): Extension<Token>,
     State(data): State<Arc<AppState>>,
+    JsonOrMultipart(settings_data): JsonOrMultipart<UserSettingsUpdateSchema>,
 ) -> crate::Result<impl IntoResponse> {
-    Ok(Json(SeniorUser::get_all(search_info, &data.database).await?))
+    let settings =
+        UserSettings::update((token.user_type(), token.user_id()), &settings_data, &data.database)
+            .await?;
+    Ok(SuccessResponse::new(settings))
 }
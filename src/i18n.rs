@@ -0,0 +1,96 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::str::FromStr;
+
+use crate::user::settings::Locale;
+
+/// Korean messages of the errors by their codes. The English messages are the
+/// ones written where the errors occur.
+const KO_ERROR_MESSAGES: &[(&str, &str)] = &[
+    ("account_exists", "이미 가입된 이메일 또는 전화번호입니다"),
+    ("banned_word", "사용할 수 없는 단어가 포함되어 있습니다"),
+    ("block_not_found", "차단한 사용자를 찾을 수 없습니다"),
+    ("consent_required", "서비스를 이용하려면 약관에 동의해야 합니다"),
+    ("consent_version_outdated", "최신 버전의 약관이 아닙니다"),
+    ("database_busy", "요청이 많아 처리하지 못했습니다. 잠시 후에 다시 시도해 주세요"),
+    ("database_error", "데이터베이스 오류가 발생했습니다"),
+    ("decryption_failed", "개인정보를 복호화하지 못했습니다"),
+    ("duplicate_entry", "이미 존재하는 데이터입니다"),
+    ("email_taken", "이미 가입된 이메일입니다"),
+    ("encryption_failed", "개인정보를 암호화하지 못했습니다"),
+    ("favorite_not_found", "즐겨찾기를 찾을 수 없습니다"),
+    ("foreign_key_violation", "존재하지 않는 데이터를 참조하거나 다른 데이터가 참조하고 있습니다"),
+    ("forbidden", "이 사용자에 접근할 권한이 없습니다"),
+    ("internal_error", "예기치 못한 오류가 발생했습니다"),
+    ("invalid_credentials", "이메일 또는 비밀번호가 올바르지 않습니다"),
+    ("invalid_body", "요청 본문이 올바르지 않습니다"),
+    ("invalid_cursor", "잘못된 커서입니다"),
+    ("invalid_major", "알 수 없는 전공입니다"),
+    ("invalid_nickname", "닉네임의 길이가 올바르지 않습니다"),
+    ("invalid_oauth_user", "잘못된 OAuth 사용자 정보입니다"),
+    ("invalid_phone", "잘못된 전화번호입니다"),
+    ("invalid_price_range", "최소 가격은 최대 가격보다 클 수 없습니다"),
+    ("invalid_query", "검색어의 길이가 올바르지 않습니다"),
+    ("invalid_report_reason", "신고 사유의 길이가 올바르지 않습니다"),
+    ("invalid_tags", "태그가 올바르지 않습니다"),
+    ("invalid_token", "토큰이 잘못되었거나 만료되었습니다"),
+    ("nickname_generation_failed", "닉네임을 생성하지 못했습니다"),
+    ("nickname_taken", "이미 사용 중인 닉네임입니다"),
+    ("no_fields_to_update", "수정할 항목이 없습니다"),
+    ("normal_user_only", "일반 사용자만 이용할 수 있습니다"),
+    ("not_logged_in", "로그인되어 있지 않습니다"),
+    ("password_hash_failed", "비밀번호를 처리하지 못했습니다"),
+    ("phone_already_verified", "이미 인증된 전화번호입니다"),
+    ("phone_taken", "이미 가입된 전화번호입니다"),
+    ("rate_limited", "요청이 너무 많습니다. 잠시 후에 다시 시도해 주세요"),
+    ("self_block", "자기 자신을 차단할 수 없습니다"),
+    ("self_report", "자기 자신을 신고할 수 없습니다"),
+    ("sms_delivery_failed", "문자 메시지를 보내지 못했습니다"),
+    ("token_creation_failed", "토큰을 생성하지 못했습니다"),
+    ("token_mismatch", "인증 정보와 사용자 정보가 일치하지 않습니다"),
+    ("token_revoked", "폐기된 토큰입니다"),
+    ("user_not_found", "사용자를 찾을 수 없습니다"),
+    ("validation_failed", "올바르지 않은 입력값이 있습니다"),
+    (
+        "verification_attempts_exceeded",
+        "인증번호를 너무 많이 틀렸습니다. 새 인증번호를 요청해 주세요",
+    ),
+    ("verification_code_mismatch", "인증번호가 일치하지 않습니다"),
+    ("verification_cooldown", "잠시 후에 인증번호를 다시 요청해 주세요"),
+    ("verification_expired", "인증번호가 만료되었습니다"),
+];
+
+/// Returns the message of the error with `code` in `locale`, or `None` if the
+/// original message should be used.
+pub fn error_message(code: &str, locale: Locale) -> Option<&'static str> {
+    match locale {
+        Locale::Ko => KO_ERROR_MESSAGES
+            .iter()
+            .find(|(error_code, _)| *error_code == code)
+            .map(|(_, message)| *message),
+        Locale::En => None,
+    }
+}
+
+/// Picks the supported locale the client prefers the most from the value of
+/// the `Accept-Language` header.
+pub fn negotiate_locale(accept_language: &str) -> Option<Locale> {
+    accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut params = range.trim().split(';');
+            let language = params.next()?.trim().split('-').next()?.to_ascii_lowercase();
+            let quality = match params.find_map(|param| param.trim().strip_prefix("q=")) {
+                Some(quality) => quality.trim().parse::<f32>().ok()?,
+                None => 1.0,
+            };
+            let locale = Locale::from_str(&language).ok()?;
+
+            (quality > 0.0).then_some((locale, quality))
+        })
+        .fold(None, |preferred: Option<(Locale, f32)>, (locale, quality)| match preferred {
+            Some((_, preferred_quality)) if preferred_quality >= quality => preferred,
+            _ => Some((locale, quality)),
+        })
+        .map(|(locale, _)| locale)
+}
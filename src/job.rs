@@ -0,0 +1,85 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::time::Duration;
+
+use sqlx::MySql;
+
+use crate::{
+    user::account::{NormalUser, SeniorUser, User},
+    Config, Result,
+};
+
+/// Interval between the runs of the purge job of the deleted accounts
+const PURGE_DELETED_USERS_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Interval between the runs of the job making the inactive accounts dormant
+const MAKE_DORMANT_USERS_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Starts the jobs running in the background while the server is alive.
+pub fn spawn_jobs(config: &Config, pool: &sqlx::Pool<MySql>) {
+    tokio::spawn(purge_deleted_users(config.account_deletion_grace_days, pool.clone()));
+    tokio::spawn(make_dormant_users(config.dormant_account_inactive_days, pool.clone()));
+}
+
+/// Encrypts the phone numbers stored before the encryption was introduced. It
+/// runs once before the server starts, since the migrations cannot encrypt
+/// them without the key.
+pub async fn encrypt_plain_phones(config: &Config, pool: &sqlx::Pool<MySql>) -> Result<()> {
+    let count = SeniorUser::encrypt_plain_phones(&config.field_cipher, pool).await?;
+    if count > 0 {
+        tracing::info!("Encrypted {} plain phone numbers", count);
+    }
+
+    Ok(())
+}
+
+/// Permanently deletes the accounts whose grace period of the deletion is over.
+async fn purge_deleted_users(grace_days: u32, pool: sqlx::Pool<MySql>) {
+    let mut interval = tokio::time::interval(PURGE_DELETED_USERS_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match NormalUser::purge_deleted(grace_days, &pool).await {
+            Ok(count) if count > 0 => tracing::info!("Purged {} deleted normal users", count),
+            Ok(_) => {}
+            Err((_, err)) => {
+                tracing::error!("Failed to purge deleted normal users: {}", err.message)
+            }
+        }
+
+        match SeniorUser::purge_deleted(grace_days, &pool).await {
+            Ok(count) if count > 0 => tracing::info!("Purged {} deleted senior users", count),
+            Ok(_) => {}
+            Err((_, err)) => {
+                tracing::error!("Failed to purge deleted senior users: {}", err.message)
+            }
+        }
+    }
+}
+
+/// Separates the personal data of the accounts nobody has logged in to for a
+/// long time, as required by the Personal Information Protection Act.
+async fn make_dormant_users(inactive_days: u32, pool: sqlx::Pool<MySql>) {
+    let mut interval = tokio::time::interval(MAKE_DORMANT_USERS_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match NormalUser::make_dormant(inactive_days, &pool).await {
+            Ok(count) if count > 0 => tracing::info!("Made {} normal users dormant", count),
+            Ok(_) => {}
+            Err((_, err)) => {
+                tracing::error!("Failed to make normal users dormant: {}", err.message)
+            }
+        }
+
+        match SeniorUser::make_dormant(inactive_days, &pool).await {
+            Ok(count) if count > 0 => tracing::info!("Made {} senior users dormant", count),
+            Ok(_) => {}
+            Err((_, err)) => {
+                tracing::error!("Failed to make senior users dormant: {}", err.message)
+            }
+        }
+    }
+}
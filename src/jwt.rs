@@ -4,24 +4,23 @@ use std::sync::Arc;
 
 use axum::{
     extract::State,
-    headers::{authorization::Bearer, Authorization},
-    http::{Request, StatusCode},
+    headers::{authorization::Bearer, Authorization, HeaderMapExt},
+    http::{request::Parts, HeaderMap, Request, StatusCode},
     middleware::Next,
     response::IntoResponse,
-    RequestPartsExt, TypedHeader,
 };
 use axum_extra::extract::CookieJar;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::ErrorResponse,
+    redact::Redacted,
     user::{
         account::{NormalUser, SeniorUser, User, UserId},
         UserType,
     },
-    AppState, Result,
+    AppState, Config, Result,
 };
 
 pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
@@ -31,6 +30,8 @@ pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
 pub struct Claims {
     /// Issuer of the JWT
     iss: String,
+    /// Recipient for which the JWT is intended
+    aud: String,
     /// Time at which the JWT was issued; can be used to determine age of the
     /// JWT
     iat: i64,
@@ -43,6 +44,9 @@ pub struct Claims {
     /// It is used to know the account type ([`NormalUser`] as "normal" and
     /// [`SeniorUser`] as "senior")
     nonce: String,
+    /// Version of the user's tokens at the time the JWT was issued. It is
+    /// compared with the stored version to reject the revoked tokens.
+    ver: u32,
 }
 
 impl Claims {
@@ -51,57 +55,82 @@ impl Claims {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Token {
     claims: Claims,
     encoded_token: String,
     user_id: UserId,
     user_type: UserType,
+    token_version: u32,
+}
+
+impl std::fmt::Debug for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Token")
+            .field("claims", &self.claims)
+            .field("encoded_token", &Redacted(&self.encoded_token))
+            .field("user_id", &self.user_id)
+            .field("user_type", &self.user_type)
+            .field("token_version", &self.token_version)
+            .finish()
+    }
 }
 
 impl Token {
     pub fn new(
-        private_key: &EncodingKey,
+        config: &Config,
         expires_in: Duration,
         user_type: UserType,
         user_id: UserId,
+        token_version: u32,
     ) -> Result<Token> {
         let claims = Claims {
-            iss: "https://respec.team/api".to_string(),
+            iss: config.jwt_issuer.clone(),
+            aud: config.jwt_audience.clone(),
             iat: Utc::now().timestamp(),
             exp: (Utc::now() + expires_in).timestamp(),
             sub: user_id.to_string(),
             nonce: user_type.to_string(),
+            ver: token_version,
         };
 
         jsonwebtoken::encode(
             &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
             &claims,
-            private_key,
+            config.private_key.encoding_key(),
         )
-        .map(|token| Ok(Token { claims, encoded_token: token, user_id, user_type }))
+        .map(|token| Ok(Token { claims, encoded_token: token, user_id, user_type, token_version }))
         .map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                ErrorResponse { status: "fail", message: "Failed to create new token".to_string() },
+                ErrorResponse {
+                    message: "Failed to create new token".to_string(),
+                    code: "token_creation_failed",
+                    invalid_fields: Vec::new(),
+                },
             )
         })?
     }
 
-    pub fn from_encoded_token(
-        encoded_token: Option<&str>,
-        public_key: &DecodingKey,
-    ) -> Result<Self> {
+    pub fn from_encoded_token(encoded_token: Option<&str>, config: &Config) -> Result<Self> {
         let encoded_token = encoded_token
             .ok_or((
                 StatusCode::BAD_REQUEST,
-                ErrorResponse { status: "fail", message: "Token does not exist".to_string() },
+                ErrorResponse {
+                    message: "Token does not exist".to_string(),
+                    code: "not_logged_in",
+                    invalid_fields: Vec::new(),
+                },
             ))
             .and_then(|encoded_token| {
                 if encoded_token.is_empty() {
                     return Err((
                         StatusCode::BAD_REQUEST,
-                        ErrorResponse { status: "fail", message: "Invalid token size".to_string() },
+                        ErrorResponse {
+                            message: "Invalid token size".to_string(),
+                            code: "invalid_token",
+                            invalid_fields: Vec::new(),
+                        },
                     ));
                 }
 
@@ -110,15 +139,16 @@ impl Token {
 
         let claims = jsonwebtoken::decode::<Claims>(
             &encoded_token,
-            public_key,
-            &jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::RS256),
+            config.public_key.decoding_key(),
+            &config.jwt_validation(),
         )
         .map_err(|_| {
             (
                 StatusCode::UNAUTHORIZED,
                 ErrorResponse {
-                    status: "fail",
                     message: "Token is invalid or expired".to_string(),
+                    code: "invalid_token",
+                    invalid_fields: Vec::new(),
                 },
             )
         })
@@ -127,17 +157,27 @@ impl Token {
         let user_id: UserId = claims.sub.parse().map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                ErrorResponse { status: "error", message: "Invalid user id".to_string() },
+                ErrorResponse {
+                    message: "Invalid user id".to_string(),
+                    code: "invalid_token",
+                    invalid_fields: Vec::new(),
+                },
             )
         })?;
         let user_type: UserType = claims.nonce.parse().map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                ErrorResponse { status: "error", message: "Invalid user type".to_string() },
+                ErrorResponse {
+                    message: "Invalid user type".to_string(),
+                    code: "invalid_token",
+                    invalid_fields: Vec::new(),
+                },
             )
         })?;
 
-        Ok(Token { claims, encoded_token, user_id, user_type })
+        let token_version = claims.ver;
+
+        Ok(Token { claims, encoded_token, user_id, user_type, token_version })
     }
 
     pub fn claims(&self) -> &Claims {
@@ -155,6 +195,26 @@ impl Token {
     pub fn user_type(&self) -> UserType {
         self.user_type
     }
+
+    pub fn token_version(&self) -> u32 {
+        self.token_version
+    }
+
+    /// Returns an error if the token was not issued to the given user.
+    pub fn verify_owner(&self, user_type: UserType, user_id: UserId) -> Result<()> {
+        if self.user_type != user_type || self.user_id != user_id {
+            return Err((
+                StatusCode::FORBIDDEN,
+                ErrorResponse {
+                    message: "You do not have permission to access this user".to_string(),
+                    code: "forbidden",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 pub async fn authorize_user<B>(
@@ -165,35 +225,77 @@ pub async fn authorize_user<B>(
 ) -> Result<impl IntoResponse> {
     let (mut parts, body) = req.into_parts();
 
-    // Find the access token in the cookies
-    //
-    // If the access token does not exists as cookie, try to find it in the
-    // Authorization header in HTTP headers
-    let access_token = match cookies.get(ACCESS_TOKEN_COOKIE) {
+    authenticate(&cookies, &data, &mut parts).await?;
+
+    // Execute the next middleware
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+/// Works like [`authorize_user`], but lets the request through without the
+/// account data when it does not carry a valid access token. It is used for
+/// the public endpoints whose response varies with the user.
+pub async fn identify_user<B>(
+    cookies: CookieJar,
+    State(data): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> impl IntoResponse {
+    let (mut parts, body) = req.into_parts();
+
+    let _ = authenticate(&cookies, &data, &mut parts).await;
+
+    next.run(Request::from_parts(parts, body)).await
+}
+
+/// Finds the access token in the cookies.
+///
+/// If the access token does not exists as cookie, try to find it in the
+/// Authorization header in HTTP headers
+pub fn find_access_token(cookies: &CookieJar, headers: &HeaderMap) -> Option<String> {
+    match cookies.get(ACCESS_TOKEN_COOKIE) {
         Some(access_token) => Some(access_token.value().to_string()),
-        None => parts
-            .extract::<TypedHeader<Authorization<Bearer>>>()
-            .await
-            .ok()
+        None => headers
+            .typed_get::<Authorization<Bearer>>()
             .map(|auth_value| auth_value.token().to_string()),
-    };
+    }
+}
 
-    let (user_id, user_type) =
-        Token::from_encoded_token(access_token.as_deref(), data.config.public_key.decoding_key())
-            .map(|token| (token.user_id(), token.user_type()))?;
+/// Verifies the access token of the request and includes the token and the
+/// account data to the extensions.
+async fn authenticate(cookies: &CookieJar, data: &AppState, parts: &mut Parts) -> Result<()> {
+    let access_token = find_access_token(cookies, &parts.headers);
+    let token = Token::from_encoded_token(access_token.as_deref(), &data.config)?;
+    let (user_id, user_type) = (token.user_id(), token.user_type());
 
-    let mut req = Request::from_parts(parts, body);
+    // Reject the tokens issued before the user revoked their sessions
+    let revoked_error = (
+        StatusCode::UNAUTHORIZED,
+        ErrorResponse {
+            message: "Token has been revoked".to_string(),
+            code: "token_revoked",
+            invalid_fields: Vec::new(),
+        },
+    );
 
     // Include the account data to extensions
     match user_type {
         UserType::NormalUser => {
-            req.extensions_mut().insert(NormalUser::from_id(user_id, &data.database).await?);
+            let user = NormalUser::from_id(user_id, &data.database).await?;
+            if token.token_version() != user.token_version() {
+                return Err(revoked_error);
+            }
+            parts.extensions.insert(user);
         }
         UserType::SeniorUser => {
-            req.extensions_mut().insert(SeniorUser::from_id(user_id, &data.database).await?);
+            let user = SeniorUser::from_id(user_id, &data.database).await?;
+            if token.token_version() != user.token_version() {
+                return Err(revoked_error);
+            }
+            parts.extensions.insert(user);
         }
     };
 
-    // Execute the next middleware
-    Ok(next.run(req).await)
+    parts.extensions.insert(token);
+
+    Ok(())
 }
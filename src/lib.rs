@@ -1,13 +1,24 @@
 // Copyright 2023. The resback authors all rights reserved.
 
 pub mod config;
+mod crypto;
 pub mod env;
 mod error;
+mod filter;
 mod handler;
+mod i18n;
+mod job;
 mod jwt;
+mod major;
 mod nickname;
 mod oauth;
+mod openapi;
+mod rate_limit;
+mod redact;
 mod schema;
+mod sms;
+mod suggestion;
+mod tag;
 mod user;
 
 use sqlx::MySql;
@@ -16,14 +27,21 @@ use std::sync::Arc;
 
 use axum::{
     middleware,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use oauth::NonStandardClient;
+use rate_limit::RateLimiter;
+use sms::SmsClient;
+use tower_http::catch_panic::CatchPanicLayer;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 pub use config::Config;
 pub use env::get_env_or_panic;
 pub use error::Result;
+pub use job::{encrypt_plain_phones, spawn_jobs};
+pub use redact::RedactedStdout;
 
 pub struct AppState {
     database: sqlx::Pool<MySql>,
@@ -35,6 +53,8 @@ pub struct AppState {
     /// Bugs:
     /// * https://github.com/ramosbugs/oauth2-rs/issues/191
     naver_oauth: NonStandardClient,
+    sms_client: SmsClient,
+    rate_limiter: RateLimiter,
 }
 
 pub fn app(config: &Config, pool: &sqlx::Pool<MySql>) -> Router {
@@ -44,27 +64,157 @@ pub fn app(config: &Config, pool: &sqlx::Pool<MySql>) -> Router {
         google_oauth: config.google_oauth.to_client(),
         kakao_oauth: config.kakao_oauth.to_client(),
         naver_oauth: config.naver_oauth.to_non_standard_client(),
+        sms_client: config.sms.to_client(),
+        rate_limiter: RateLimiter::default(),
     });
 
     let auth_layer = middleware::from_fn_with_state(app_state.clone(), jwt::authorize_user);
+    let identify_layer = middleware::from_fn_with_state(app_state.clone(), jwt::identify_user);
 
     let root_routers = Router::new().route("/", get(handler::root));
+    let docs_routers = SwaggerUi::new(openapi::SWAGGER_UI_PATH)
+        .url(openapi::OPENAPI_JSON_PATH, openapi::ApiDoc::openapi());
     let auth_routers = Router::new()
         .route("/auth/:provider", post(handler::auth::auth_provider))
         .route("/auth/senior", post(handler::auth::auth_senior))
+        .route("/auth/senior/recovery", post(handler::auth::request_senior_recovery))
+        .route("/auth/senior/recovery/confirm", post(handler::auth::complete_senior_recovery))
         .route("/auth/token", patch(handler::auth::auth_refresh).route_layer(auth_layer.clone()))
-        .route("/auth/token", delete(handler::auth::logout_user).route_layer(auth_layer.clone()));
+        .route("/auth/token", delete(handler::auth::logout_user).route_layer(auth_layer.clone()))
+        .route(
+            "/auth/sessions",
+            delete(handler::auth::logout_all_sessions).route_layer(auth_layer.clone()),
+        );
+    let majors_routers = Router::new().route("/majors", get(handler::majors::get_majors));
+    let tags_routers = Router::new().route("/tags/popular", get(handler::tags::get_popular_tags));
     let users_routers = Router::new()
+        .route("/users/senior", post(handler::users::register_senior_user))
         .route(
             "/users/senior",
-            post(handler::users::register_senior_user).get(handler::users::get_seniors),
+            get(handler::users::get_seniors).route_layer(identify_layer.clone()),
+        )
+        .route("/users/senior/suggest", get(handler::users::suggest_seniors))
+        .route(
+            "/users/senior/recommended",
+            get(handler::users::get_recommended_seniors).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id",
+            get(handler::users::get_senior_user_info).route_layer(identify_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id",
+            delete(handler::users::delete_senior_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id",
+            patch(handler::users::update_senior_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id/nickname/regenerate",
+            post(handler::users::regenerate_senior_user_nickname).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id/phone/verification",
+            post(handler::users::send_phone_verification).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id/phone/verification/confirm",
+            post(handler::users::confirm_phone_verification).route_layer(auth_layer.clone()),
+        )
+        .route("/users/senior/:id/tags", get(handler::users::get_senior_tags))
+        .route(
+            "/users/senior/:id/tags",
+            put(handler::users::update_senior_tags).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id/block",
+            post(handler::users::block_senior_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id/block",
+            delete(handler::users::unblock_senior_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/senior/:id/report",
+            post(handler::users::report_senior_user).route_layer(auth_layer.clone()),
+        )
+        .route("/users/nickname/availability", get(handler::users::check_nickname_availability))
+        .route(
+            "/users/me/consents",
+            get(handler::users::get_pending_consents).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/me/consents",
+            post(handler::users::answer_consent).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/me/settings",
+            get(handler::users::get_user_settings).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/me/settings",
+            put(handler::users::update_user_settings).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/me/recently-viewed",
+            get(handler::users::get_recently_viewed_seniors).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/me/export",
+            get(handler::users::export_user_data).route_layer(auth_layer.clone()),
         )
-        .route("/users/senior/:id", get(handler::users::get_senior_user_info))
-        .route("/users/senior/:id", delete(handler::users::delete_senior_user))
         .route("/users/normal/:id", get(handler::users::get_normal_user_info))
-        .route("/users/normal/:id", delete(handler::users::delete_normal_user));
+        .route(
+            "/users/normal/:id",
+            delete(handler::users::delete_normal_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id",
+            patch(handler::users::update_normal_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/nickname/regenerate",
+            post(handler::users::regenerate_normal_user_nickname).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/block",
+            post(handler::users::block_normal_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/block",
+            delete(handler::users::unblock_normal_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/report",
+            post(handler::users::report_normal_user).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/favorites",
+            get(handler::users::get_favorite_seniors).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/favorites/:senior_id",
+            post(handler::users::add_favorite_senior).route_layer(auth_layer.clone()),
+        )
+        .route(
+            "/users/normal/:id/favorites/:senior_id",
+            delete(handler::users::remove_favorite_senior).route_layer(auth_layer.clone()),
+        );
 
-    Router::new().merge(root_routers).merge(auth_routers).merge(users_routers).with_state(app_state)
+    Router::new()
+        .merge(root_routers)
+        .merge(docs_routers)
+        .merge(auth_routers)
+        .merge(majors_routers)
+        .merge(tags_routers)
+        .merge(users_routers)
+        .layer(middleware::from_fn_with_state(app_state.clone(), rate_limit::limit_rate))
+        // Placed inside `problem_details` so that the panics are reported the
+        // same way as the other errors
+        .layer(CatchPanicLayer::custom(error::panic_response))
+        .layer(middleware::from_fn_with_state(app_state.clone(), error::problem_details))
+        .with_state(app_state)
 }
 
 pub fn about() -> String {
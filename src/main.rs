@@ -1,5 +1,7 @@
 // Copyright 2023. The resback authors all rights reserved.
 
+use std::net::SocketAddr;
+
 use axum::Server;
 use dotenvy::dotenv;
 use resback::get_env_or_panic;
@@ -13,7 +15,7 @@ async fn main() {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "resback=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(resback::RedactedStdout))
         .init();
 
     // Load environment variables from '.env' file
@@ -42,10 +44,21 @@ async fn main() {
         std::process::exit(1);
     }
 
+    if let Err((_, err)) = resback::encrypt_plain_phones(&config, &pool).await {
+        println!("Failed to encrypt phone numbers: {}", err.message);
+        std::process::exit(1);
+    }
+
+    resback::spawn_jobs(&config, &pool);
+
     let app = resback::app(&config, &pool);
 
     print_server_started(&config.address);
-    Server::bind(&config.address.parse().unwrap()).serve(app.into_make_service()).await.unwrap();
+    // The rate limits count the anonymous requests by the address of the peer
+    Server::bind(&config.address.parse().unwrap())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
 }
 
 fn print_server_started(address: &str) {
@@ -0,0 +1,88 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use sqlx::MySql;
+
+use crate::{
+    error::{database_error, ErrorResponse},
+    schema::{MajorCategorySchema, MajorSchema},
+    Result,
+};
+
+pub type MajorId = u32;
+
+#[derive(Debug, sqlx::FromRow)]
+struct MajorRow {
+    id: MajorId,
+    /// Category of the major. The categories themselves have no parent.
+    parent_id: Option<MajorId>,
+    name: String,
+    senior_count: i64,
+}
+
+/// Majors of the seniors, grouped into categories
+pub struct Major;
+
+impl Major {
+    /// Returns every category with its majors and the number of seniors in
+    /// each of them.
+    pub async fn get_all(pool: &sqlx::Pool<MySql>) -> Result<Vec<MajorCategorySchema>> {
+        let rows = sqlx::query_as_unchecked!(
+            MajorRow,
+            "SELECT id, parent_id, name, (SELECT COUNT(*) FROM senior_users WHERE senior_users.major = majors.name AND deleted_at IS NULL AND dormant_at IS NULL) AS senior_count FROM majors ORDER BY id"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)?;
+
+        let (categories, majors): (Vec<MajorRow>, Vec<MajorRow>) =
+            rows.into_iter().partition(|row| row.parent_id.is_none());
+
+        Ok(categories
+            .into_iter()
+            .map(|category| {
+                let majors: Vec<MajorSchema> = majors
+                    .iter()
+                    .filter(|major| major.parent_id == Some(category.id))
+                    .map(|major| MajorSchema {
+                        id: major.id,
+                        name: major.name.clone(),
+                        senior_count: major.senior_count as u32,
+                    })
+                    .collect();
+
+                MajorCategorySchema {
+                    id: category.id,
+                    name: category.name,
+                    senior_count: category.senior_count as u32
+                        + majors.iter().map(|major| major.senior_count).sum::<u32>(),
+                    majors,
+                }
+            })
+            .collect())
+    }
+
+    /// Checks the name is one of the majors the seniors can choose. The
+    /// categories cannot be chosen.
+    pub async fn validate(name: &str, pool: &sqlx::Pool<MySql>) -> Result<()> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM majors WHERE name = ? AND parent_id IS NOT NULL",
+            name
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(database_error)?;
+
+        match count {
+            1.. => Ok(()),
+            _ => Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: format!("Unknown major: {}", name),
+                    code: "invalid_major",
+                    invalid_fields: Vec::new(),
+                },
+            )),
+        }
+    }
+}
@@ -14,9 +14,9 @@ use oauth2::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::env::get_env_or_panic;
+use crate::{env::get_env_or_panic, redact::Redacted};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, sqlx::Type, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum OAuthProvider {
     Google,
@@ -111,7 +111,7 @@ impl OAuthConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct GoogleUser {
     pub id: String,
     pub email: String,
@@ -123,6 +123,21 @@ pub struct GoogleUser {
     pub locale: String,
 }
 
+impl std::fmt::Debug for GoogleUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GoogleUser")
+            .field("id", &Redacted(&self.id))
+            .field("email", &Redacted(&self.email))
+            .field("verified_email", &self.verified_email)
+            .field("name", &Redacted(&self.name))
+            .field("given_name", &Redacted(&self.given_name))
+            .field("family_name", &Redacted(&self.family_name))
+            .field("picture", &self.picture)
+            .field("locale", &self.locale)
+            .finish()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KakaoUser {
     pub id: u64,
@@ -0,0 +1,174 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{
+    error::ProblemDetails,
+    handler,
+    jwt::ACCESS_TOKEN_COOKIE,
+    oauth::OAuthProvider,
+    schema::{
+        AccountRecoveryCompleteSchema, AccountRecoveryRequestSchema, BlockExportSchema,
+        ConsentAnswerSchema, ConsentExportSchema, FavoriteExportSchema, MajorCategoryListResponse,
+        MajorCategorySchema, MajorSchema, NicknameAvailabilityResponse, NicknameAvailabilitySchema,
+        NormalLoginSchema, NormalUpdateSchema, NormalUserExportSchema, NormalUserInfoResponse,
+        NormalUserInfoSchema, PageMeta, PendingConsentListResponse, PendingConsentSchema,
+        PhoneVerificationSchema, RecentlyViewedExportSchema, ReportExportSchema, SeniorLoginSchema,
+        SeniorRegisterSchema, SeniorSearchSort, SeniorTagsSchema, SeniorUpdateSchema,
+        SeniorUserExportSchema, SeniorUserInfoListResponse, SeniorUserInfoResponse,
+        SeniorUserInfoSchema, StringListResponse, SuggestionListResponse, SuggestionSchema,
+        TagListResponse, TagSchema, UserDataExportResponse, UserDataExportSchema,
+        UserIdentificationResponse, UserIdentificationSchema, UserNicknameResponse,
+        UserNicknameSchema, UserProfileExportSchema, UserReportSchema, UserSettingsResponse,
+        UserSettingsSchema, UserSettingsUpdateSchema,
+    },
+    suggestion::SuggestionKind,
+    user::{
+        consent::ConsentType,
+        settings::{ContactChannel, Locale, Theme},
+        UserType,
+    },
+};
+
+/// Path of the OpenAPI document
+pub const OPENAPI_JSON_PATH: &str = "/api-docs/openapi.json";
+/// Path of the Swagger UI rendering the OpenAPI document
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handler::root::root,
+        handler::auth::auth_provider,
+        handler::auth::auth_senior,
+        handler::auth::request_senior_recovery,
+        handler::auth::complete_senior_recovery,
+        handler::auth::auth_refresh,
+        handler::auth::logout_user,
+        handler::auth::logout_all_sessions,
+        handler::majors::get_majors,
+        handler::tags::get_popular_tags,
+        handler::users::register_senior_user,
+        handler::users::get_seniors,
+        handler::users::suggest_seniors,
+        handler::users::get_recommended_seniors,
+        handler::users::get_senior_user_info,
+        handler::users::delete_senior_user,
+        handler::users::update_senior_user,
+        handler::users::regenerate_senior_user_nickname,
+        handler::users::send_phone_verification,
+        handler::users::confirm_phone_verification,
+        handler::users::get_senior_tags,
+        handler::users::update_senior_tags,
+        handler::users::block_senior_user,
+        handler::users::unblock_senior_user,
+        handler::users::report_senior_user,
+        handler::users::check_nickname_availability,
+        handler::users::get_pending_consents,
+        handler::users::answer_consent,
+        handler::users::get_user_settings,
+        handler::users::update_user_settings,
+        handler::users::get_recently_viewed_seniors,
+        handler::users::export_user_data,
+        handler::users::get_normal_user_info,
+        handler::users::delete_normal_user,
+        handler::users::update_normal_user,
+        handler::users::regenerate_normal_user_nickname,
+        handler::users::block_normal_user,
+        handler::users::unblock_normal_user,
+        handler::users::report_normal_user,
+        handler::users::get_favorite_seniors,
+        handler::users::add_favorite_senior,
+        handler::users::remove_favorite_senior,
+    ),
+    components(schemas(
+        ProblemDetails,
+        UserType,
+        OAuthProvider,
+        ConsentType,
+        ContactChannel,
+        Locale,
+        Theme,
+        SuggestionKind,
+        SeniorSearchSort,
+        NormalLoginSchema,
+        SeniorLoginSchema,
+        SeniorRegisterSchema,
+        NormalUpdateSchema,
+        SeniorUpdateSchema,
+        SeniorTagsSchema,
+        PhoneVerificationSchema,
+        ConsentAnswerSchema,
+        UserSettingsUpdateSchema,
+        AccountRecoveryRequestSchema,
+        AccountRecoveryCompleteSchema,
+        UserReportSchema,
+        UserIdentificationSchema,
+        NormalUserInfoSchema,
+        SeniorUserInfoSchema,
+        SuggestionSchema,
+        UserNicknameSchema,
+        NicknameAvailabilitySchema,
+        PendingConsentSchema,
+        UserSettingsSchema,
+        MajorCategorySchema,
+        MajorSchema,
+        TagSchema,
+        UserDataExportSchema,
+        UserProfileExportSchema,
+        NormalUserExportSchema,
+        SeniorUserExportSchema,
+        FavoriteExportSchema,
+        RecentlyViewedExportSchema,
+        ConsentExportSchema,
+        BlockExportSchema,
+        ReportExportSchema,
+        PageMeta,
+        UserIdentificationResponse,
+        NormalUserInfoResponse,
+        SeniorUserInfoResponse,
+        SeniorUserInfoListResponse,
+        SuggestionListResponse,
+        StringListResponse,
+        UserNicknameResponse,
+        NicknameAvailabilityResponse,
+        PendingConsentListResponse,
+        UserSettingsResponse,
+        UserDataExportResponse,
+        MajorCategoryListResponse,
+        TagListResponse,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "root", description = "Information of the service"),
+        (name = "auth", description = "Logins and sessions"),
+        (name = "users", description = "Normal and senior users"),
+        (name = "majors", description = "Majors of the seniors"),
+        (name = "tags", description = "Tags of the senior profiles"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the ways the access token is sent, which are the cookie and the
+/// Authorization header.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "access_token",
+                SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(ACCESS_TOKEN_COOKIE))),
+            );
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build(),
+                ),
+            );
+        }
+    }
+}
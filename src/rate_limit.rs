@@ -0,0 +1,208 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use crate::{
+    error::ErrorResponse,
+    jwt::{self, Token},
+    user::{account::UserId, UserType},
+    AppState,
+};
+
+/// Length of the windows the requests are counted in
+const WINDOW: Duration = Duration::from_secs(60);
+/// Number of the counted clients above which the expired windows are removed
+const PRUNE_THRESHOLD: usize = 10_000;
+/// Header the reverse proxies append the address of the client to
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Groups of the endpoints sharing a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Bucket {
+    General,
+    /// Logins and sessions, which are the targets of credential stuffing
+    Auth,
+    /// Endpoints sending or checking the verification codes. Every message
+    /// costs money and the codes are short enough to be guessed.
+    Verification,
+}
+
+impl Bucket {
+    fn of(path: &str) -> Self {
+        if path.starts_with("/auth/senior/recovery")
+            || path.ends_with("/phone/verification")
+            || path.ends_with("/phone/verification/confirm")
+        {
+            Self::Verification
+        } else if path.starts_with("/auth/") {
+            Self::Auth
+        } else {
+            Self::General
+        }
+    }
+
+    /// Number of the requests a client can make in a window
+    fn limit(self) -> u32 {
+        match self {
+            Self::General => 300,
+            Self::Auth => 20,
+            Self::Verification => 5,
+        }
+    }
+}
+
+/// Who the requests are counted for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Client {
+    User(UserType, UserId),
+    Ip(IpAddr),
+    /// The address is not known when the app is not served over a socket,
+    /// such as in the tests. Every such request shares the same window.
+    Unknown,
+}
+
+impl Client {
+    /// Returns the address the request comes from. Behind a reverse proxy,
+    /// the peer is the proxy itself, so the address is taken from the last
+    /// entry of `X-Forwarded-For`, which is the one the proxy appended. The
+    /// entries before it are written by the client and cannot be trusted.
+    fn address<B>(
+        request: &Request<B>,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+        trust_forwarded_for: bool,
+    ) -> Self {
+        let forwarded = trust_forwarded_for
+            .then(|| request.headers().get_all(X_FORWARDED_FOR).iter().last())
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|address| address.trim().parse::<IpAddr>().ok());
+
+        match (forwarded, connect_info) {
+            (Some(address), _) => Self::Ip(address),
+            (None, Some(ConnectInfo(address))) => Self::Ip(address.ip()),
+            (None, None) => Self::Unknown,
+        }
+    }
+}
+
+struct Window {
+    started_at: Instant,
+    count: u32,
+}
+
+/// Counts the requests of every client in fixed windows kept in memory
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<(Bucket, Client), Window>>,
+}
+
+impl RateLimiter {
+    /// Counts a request of the client, and returns how long the client has
+    /// to wait if it is over the limit.
+    fn hit(&self, bucket: Bucket, client: Client) -> Option<Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+
+        if windows.len() > PRUNE_THRESHOLD {
+            windows.retain(|_, window| now.duration_since(window.started_at) < WINDOW);
+        }
+
+        let window =
+            windows.entry((bucket, client)).or_insert(Window { started_at: now, count: 0 });
+        if now.duration_since(window.started_at) >= WINDOW {
+            *window = Window { started_at: now, count: 0 };
+        }
+
+        if window.count >= bucket.limit() {
+            return Some(WINDOW - now.duration_since(window.started_at));
+        }
+        window.count += 1;
+
+        None
+    }
+}
+
+/// Middleware rejecting the clients making too many requests with
+/// `429 Too Many Requests`. Every request is counted for its IP address, and
+/// also for the account if the user is authenticated, so that neither
+/// rotating the accounts nor the addresses gets around the limit.
+pub async fn limit_rate<B>(
+    cookies: CookieJar,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    State(data): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let bucket = Bucket::of(request.uri().path());
+
+    let address = Client::address(&request, connect_info, data.config.trust_forwarded_for);
+    let mut retry_after = data.rate_limiter.hit(bucket, address);
+
+    let access_token = jwt::find_access_token(&cookies, request.headers());
+    if let Ok(token) = Token::from_encoded_token(access_token.as_deref(), &data.config) {
+        let user = Client::User(token.user_type(), token.user_id());
+        retry_after = retry_after.max(data.rate_limiter.hit(bucket, user));
+    }
+
+    match retry_after {
+        Some(retry_after) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, (retry_after.as_secs_f64().ceil() as u64).to_string())],
+            ErrorResponse {
+                message: "Too many requests, try again later".to_string(),
+                code: "rate_limited",
+                invalid_fields: Vec::new(),
+            },
+        )
+            .into_response(),
+        None => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forwarded_request(forwarded_for: &[&str]) -> Request<()> {
+        let mut builder = Request::builder();
+        for value in forwarded_for {
+            builder = builder.header(X_FORWARDED_FOR, *value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    fn peer() -> Option<ConnectInfo<SocketAddr>> {
+        Some(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 443))))
+    }
+
+    #[test]
+    fn address_of_the_peer() {
+        let request = forwarded_request(&["203.0.113.1"]);
+        assert_eq!(Client::address(&request, peer(), false), Client::Ip([10, 0, 0, 1].into()));
+        assert_eq!(Client::address(&request, None, false), Client::Unknown);
+    }
+
+    #[test]
+    fn address_appended_by_the_proxy() {
+        // Only the last entry is appended by the proxy
+        let request = forwarded_request(&["198.51.100.1", "192.0.2.1, 203.0.113.1"]);
+        assert_eq!(Client::address(&request, peer(), true), Client::Ip([203, 0, 113, 1].into()));
+
+        let request = forwarded_request(&["not an address"]);
+        assert_eq!(Client::address(&request, peer(), true), Client::Ip([10, 0, 0, 1].into()));
+    }
+}
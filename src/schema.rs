@@ -1,72 +1,612 @@
 // Copyright 2023. The resback authors all rights reserved.
 
-use axum::{async_trait, extract::multipart};
-use axum_typed_multipart::{TryFromMultipart, TypedMultipartError};
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{multipart, FromRequest, FromRequestParts, Query},
+    http::{header, request::Parts, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_typed_multipart::{TryFromMultipart, TypedMultipart, TypedMultipartError};
+use chrono::{DateTime, Utc};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
 use crate::{
+    error::ErrorResponse,
+    major::MajorId,
     oauth::OAuthProvider,
-    user::{account::UserId, UserType},
+    redact::Redacted,
+    suggestion::SuggestionKind,
+    user::{
+        account::UserId,
+        consent::ConsentType,
+        settings::{ContactChannel, Locale, Theme},
+        UserType,
+    },
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart)]
+/// Number of items in a page when the client does not specify the limit
+pub const DEFAULT_PAGE_LIMIT: u32 = 20;
+/// Maximum number of items the client can request in a page
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// Checks the constraints declared on the schema, and lists every invalid
+/// field in the error.
+pub fn validate<T: Validate>(data: &T) -> crate::Result<()> {
+    data.validate().map_err(|errors| {
+        let mut fields: Vec<&'static str> = errors.field_errors().into_keys().collect();
+        fields.sort_unstable();
+
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorResponse {
+                message: format!("Invalid fields: {}", fields.join(", ")),
+                code: "validation_failed",
+                invalid_fields: fields,
+            },
+        )
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
 pub struct NormalLoginSchema {
     pub code: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart)]
+impl std::fmt::Debug for NormalLoginSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NormalLoginSchema").field("code", &Redacted(&self.code)).finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, Validate, ToSchema)]
 pub struct SeniorRegisterSchema {
+    #[validate(email)]
     pub email: String,
+    #[validate(length(min = 1))]
     pub password: String,
+    #[validate(length(min = 1))]
     pub name: String,
     pub phone: String,
     pub major: String,
+    #[validate(range(min = 0, max = 70))]
     pub experience_years: i32,
+    #[validate(range(min = 0, max = 10_000_000))]
     pub mentoring_price: i32,
+    #[schema(value_type = Vec<String>)]
     pub representative_careers: JsonArray<String>,
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart)]
+impl std::fmt::Debug for SeniorRegisterSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeniorRegisterSchema")
+            .field("email", &Redacted(&self.email))
+            .field("password", &Redacted(&self.password))
+            .field("name", &Redacted(&self.name))
+            .field("phone", &Redacted(&self.phone))
+            .field("major", &self.major)
+            .field("experience_years", &self.experience_years)
+            .field("mentoring_price", &self.mentoring_price)
+            .field("representative_careers", &self.representative_careers)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+/// Fields of a normal user profile to update. The omitted fields are kept as
+/// they are.
+#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
+pub struct NormalUpdateSchema {
+    pub nickname: Option<String>,
+}
+
+/// Fields of a senior user profile to update. The omitted fields are kept as
+/// they are.
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, Validate, ToSchema)]
+pub struct SeniorUpdateSchema {
+    pub nickname: Option<String>,
+    pub phone: Option<String>,
+    pub major: Option<String>,
+    #[validate(range(min = 0, max = 70))]
+    pub experience_years: Option<i32>,
+    #[validate(range(min = 0, max = 10_000_000))]
+    pub mentoring_price: Option<i32>,
+    #[schema(value_type = Option<Vec<String>>)]
+    pub representative_careers: Option<JsonArray<String>>,
+    pub description: Option<String>,
+}
+
+impl std::fmt::Debug for SeniorUpdateSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeniorUpdateSchema")
+            .field("nickname", &self.nickname)
+            .field("phone", &Redacted(&self.phone))
+            .field("major", &self.major)
+            .field("experience_years", &self.experience_years)
+            .field("mentoring_price", &self.mentoring_price)
+            .field("representative_careers", &self.representative_careers)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
+pub struct SeniorTagsSchema {
+    #[schema(value_type = Vec<String>)]
+    pub tags: JsonArray<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
+pub struct PhoneVerificationSchema {
+    pub code: String,
+}
+
+impl std::fmt::Debug for PhoneVerificationSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PhoneVerificationSchema").field("code", &Redacted(&self.code)).finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
+pub struct ConsentAnswerSchema {
+    pub consent_type: ConsentType,
+    pub version: u32,
+    pub agreed: bool,
+}
+
+/// Every setting of a user, which replaces the current settings as a whole
+#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
+pub struct UserSettingsUpdateSchema {
+    pub email_notification: bool,
+    pub sms_notification: bool,
+    pub push_notification: bool,
+    pub contact_channel: ContactChannel,
+    pub language: Locale,
+    pub theme: Theme,
+}
+
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, Validate, ToSchema)]
 pub struct SeniorLoginSchema {
+    #[validate(length(min = 1))]
     pub email: String,
+    #[validate(length(min = 1))]
     pub password: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart)]
+impl std::fmt::Debug for SeniorLoginSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeniorLoginSchema")
+            .field("email", &Redacted(&self.email))
+            .field("password", &Redacted(&self.password))
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, Validate, ToSchema)]
+pub struct AccountRecoveryRequestSchema {
+    #[validate(email)]
+    pub email: String,
+}
+
+impl std::fmt::Debug for AccountRecoveryRequestSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountRecoveryRequestSchema")
+            .field("email", &Redacted(&self.email))
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, TryFromMultipart, Validate, ToSchema)]
+pub struct AccountRecoveryCompleteSchema {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub code: String,
+    #[validate(length(min = 1))]
+    pub password: String,
+}
+
+impl std::fmt::Debug for AccountRecoveryCompleteSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountRecoveryCompleteSchema")
+            .field("email", &Redacted(&self.email))
+            .field("code", &Redacted(&self.code))
+            .field("password", &Redacted(&self.password))
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
+pub struct UserReportSchema {
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TryFromMultipart, ToSchema)]
 pub struct UserIdentificationSchema {
     pub user_type: UserType,
+    #[schema(value_type = u64)]
     pub id: UserId,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct NormalUserInfoSchema {
+    #[schema(value_type = u64)]
     pub id: UserId,
     pub oauth_provider: OAuthProvider,
     pub nickname: String,
     pub picture: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct SeniorUserInfoSchema {
+    #[schema(value_type = u64)]
     pub id: UserId,
     pub nickname: String,
     pub picture: String,
     pub major: String,
     pub experience_years: i32,
     pub mentoring_price: i32,
+    #[schema(value_type = Vec<String>)]
     pub representative_careers: JsonArray<String>,
     pub description: String,
+    pub view_count: u32,
+    /// Whether the authenticated normal user saved the senior as a favorite.
+    /// It is omitted for the anonymous requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_favorite: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SeniorSearchSchema {
     pub major: Option<String>,
+    pub tag: Option<String>,
+    #[validate(range(min = 0))]
+    pub min_price: Option<i32>,
+    #[validate(range(min = 0))]
+    pub max_price: Option<i32>,
+    pub sort: Option<SeniorSearchSort>,
 }
 
+/// Sorting orders of the senior search results
+#[derive(Debug, Deserialize, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeniorSearchSort {
+    PriceAsc,
+    PriceDesc,
+    Experience,
+    Newest,
+    /// The most viewed seniors in the last 7 days first
+    Popular,
+}
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestionQuery {
+    pub q: String,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct SuggestionSchema {
+    pub kind: SuggestionKind,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct UserNicknameSchema {
+    pub user_type: UserType,
+    #[schema(value_type = u64)]
+    pub id: UserId,
+    pub nickname: String,
+}
+
+#[derive(Debug, Deserialize, Clone, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NicknameAvailabilityQuery {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PendingConsentSchema {
+    pub consent_type: ConsentType,
+    pub version: u32,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct UserSettingsSchema {
+    pub email_notification: bool,
+    pub sms_notification: bool,
+    pub push_notification: bool,
+    pub contact_channel: ContactChannel,
+    pub language: Locale,
+    pub theme: Theme,
+}
+
+impl Default for UserSettingsSchema {
+    fn default() -> Self {
+        Self {
+            email_notification: true,
+            sms_notification: true,
+            push_notification: true,
+            contact_channel: ContactChannel::Email,
+            language: Locale::Ko,
+            theme: Theme::System,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct MajorCategorySchema {
+    #[schema(value_type = u32)]
+    pub id: MajorId,
+    pub name: String,
+    /// Number of the seniors in every major of the category
+    pub senior_count: u32,
+    pub majors: Vec<MajorSchema>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct MajorSchema {
+    #[schema(value_type = u32)]
+    pub id: MajorId,
+    pub name: String,
+    pub senior_count: u32,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct TagSchema {
+    pub name: String,
+    pub senior_count: u32,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct NicknameAvailabilitySchema {
+    pub nickname: String,
+    pub available: bool,
+}
+
+/// Every personal data we keep about a user, exported on their request
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct UserDataExportSchema {
+    pub user_type: UserType,
+    pub profile: UserProfileExportSchema,
+    pub favorites: Vec<FavoriteExportSchema>,
+    pub recently_viewed: Vec<RecentlyViewedExportSchema>,
+    pub blocked_users: Vec<BlockExportSchema>,
+    pub reports: Vec<ReportExportSchema>,
+    pub consents: Vec<ConsentExportSchema>,
+    pub settings: UserSettingsSchema,
+    pub exported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+#[serde(untagged)]
+pub enum UserProfileExportSchema {
+    Normal(NormalUserExportSchema),
+    Senior(SeniorUserExportSchema),
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct NormalUserExportSchema {
+    #[schema(value_type = u64)]
+    pub id: UserId,
+    pub oauth_provider: OAuthProvider,
+    pub oauth_id: String,
+    pub nickname: String,
+    pub picture: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct SeniorUserExportSchema {
+    #[schema(value_type = u64)]
+    pub id: UserId,
+    pub email: String,
+    pub name: String,
+    pub phone: String,
+    pub phone_verified: bool,
+    pub nickname: String,
+    pub picture: String,
+    pub major: String,
+    pub experience_years: i32,
+    pub mentoring_price: i32,
+    #[schema(value_type = Vec<String>)]
+    pub representative_careers: JsonArray<String>,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct FavoriteExportSchema {
+    #[schema(value_type = u64)]
+    pub senior_user_id: UserId,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct RecentlyViewedExportSchema {
+    #[schema(value_type = u64)]
+    pub senior_user_id: UserId,
+    pub viewed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ConsentExportSchema {
+    pub consent_type: ConsentType,
+    pub version: u32,
+    pub agreed: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct BlockExportSchema {
+    pub blocked_user_type: UserType,
+    #[schema(value_type = u64)]
+    pub blocked_user_id: UserId,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct ReportExportSchema {
+    pub reported_type: UserType,
+    #[schema(value_type = u64)]
+    pub reported_id: UserId,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters shared by the endpoints that return a list of items
+#[derive(Debug, Deserialize, Clone, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CursorParams {
+    /// Opaque cursor taken from `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+impl CursorParams {
+    /// Returns the number of items to fetch, clamped to [`MAX_PAGE_LIMIT`].
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CursorParams
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, ErrorResponse);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Query::<Self>::from_request_parts(parts, state).await.map(|Query(params)| params).map_err(
+            |err| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    ErrorResponse {
+                        message: format!("Invalid cursor: {}", err),
+                        code: "invalid_cursor",
+                        invalid_fields: Vec::new(),
+                    },
+                )
+            },
+        )
+    }
+}
+
+/// Request body sent either as `application/json` or as `multipart/form-data`.
+///
+/// The schemas carrying files should keep using [`TypedMultipart`], since the
+/// files cannot be sent in JSON.
+#[derive(Debug, Clone)]
+pub struct JsonOrMultipart<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S, Body> for JsonOrMultipart<T>
+where
+    T: DeserializeOwned + TryFromMultipart,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, ErrorResponse);
+
+    async fn from_request(req: Request<Body>, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
+
+        if is_json {
+            Json::<T>::from_request(req, state).await.map(|Json(data)| Self(data)).map_err(|err| {
+                (
+                    err.status(),
+                    ErrorResponse {
+                        message: err.body_text(),
+                        code: "invalid_body",
+                        invalid_fields: Vec::new(),
+                    },
+                )
+            })
+        } else {
+            TypedMultipart::<T>::from_request(req, state)
+                .await
+                .map(|TypedMultipart(data)| Self(data))
+                .map_err(|err| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        ErrorResponse {
+                            message: err.to_string(),
+                            code: "invalid_body",
+                            invalid_fields: Vec::new(),
+                        },
+                    )
+                })
+        }
+    }
+}
+
+/// A page of items returned by the list endpoints
 #[derive(Debug, Serialize, Clone)]
-pub struct SeniorSearchResultSchema {
-    pub seniors: Vec<SeniorUserInfoSchema>,
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Cursor to fetch the next page, or `None` if this is the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Body of every successful response, so that the clients parse all of them
+/// the same way
+#[derive(Debug, Serialize, Clone, ToSchema)]
+#[aliases(
+    UserIdentificationResponse = SuccessResponse<UserIdentificationSchema>,
+    NormalUserInfoResponse = SuccessResponse<NormalUserInfoSchema>,
+    SeniorUserInfoResponse = SuccessResponse<SeniorUserInfoSchema>,
+    SeniorUserInfoListResponse = SuccessResponse<Vec<SeniorUserInfoSchema>>,
+    SuggestionListResponse = SuccessResponse<Vec<SuggestionSchema>>,
+    StringListResponse = SuccessResponse<Vec<String>>,
+    UserNicknameResponse = SuccessResponse<UserNicknameSchema>,
+    NicknameAvailabilityResponse = SuccessResponse<NicknameAvailabilitySchema>,
+    PendingConsentListResponse = SuccessResponse<Vec<PendingConsentSchema>>,
+    UserSettingsResponse = SuccessResponse<UserSettingsSchema>,
+    UserDataExportResponse = SuccessResponse<UserDataExportSchema>,
+    MajorCategoryListResponse = SuccessResponse<Vec<MajorCategorySchema>>,
+    TagListResponse = SuccessResponse<Vec<TagSchema>>
+)]
+pub struct SuccessResponse<T> {
+    pub data: T,
+    /// Pagination of the list endpoints. It is omitted for the other ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<PageMeta>,
+}
+
+impl<T> SuccessResponse<T> {
+    pub fn new(data: T) -> Self {
+        Self { data, meta: None }
+    }
+}
+
+impl<T> From<Paginated<T>> for SuccessResponse<Vec<T>> {
+    fn from(page: Paginated<T>) -> Self {
+        Self { data: page.items, meta: Some(PageMeta { next_cursor: page.next_cursor }) }
+    }
+}
+
+impl<T: Serialize> IntoResponse for SuccessResponse<T> {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PageMeta {
+    /// Cursor to fetch the next page, or `None` if this is the last page
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,78 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use serde::Serialize;
+
+use crate::{env::get_env_or_panic, error::ErrorResponse, redact::Redacted, Result};
+
+#[derive(Clone)]
+pub struct SmsConfig {
+    api_uri: String,
+    api_key: String,
+    /// Phone number registered to the provider to send the messages from
+    sender: String,
+}
+
+impl std::fmt::Debug for SmsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmsConfig")
+            .field("api_uri", &self.api_uri)
+            .field("api_key", &Redacted(&self.api_key))
+            .field("sender", &self.sender)
+            .finish()
+    }
+}
+
+impl SmsConfig {
+    pub fn init() -> Self {
+        Self {
+            api_uri: get_env_or_panic("SMS_API_URI"),
+            api_key: get_env_or_panic("SMS_API_KEY"),
+            sender: get_env_or_panic("SMS_SENDER_NUMBER"),
+        }
+    }
+
+    pub fn to_client(&self) -> SmsClient {
+        SmsClient { http_client: reqwest::Client::new(), config: self.clone() }
+    }
+}
+
+#[derive(Serialize)]
+struct SmsMessage<'a> {
+    from: &'a str,
+    to: &'a str,
+    text: &'a str,
+}
+
+/// A client sending text messages through the HTTP API of the SMS provider
+pub struct SmsClient {
+    http_client: reqwest::Client,
+    config: SmsConfig,
+}
+
+impl SmsClient {
+    /// Sends a text message to the phone number in the E.164 format.
+    pub async fn send(&self, to: &str, text: &str) -> Result<()> {
+        let send_error = |message: String| {
+            (
+                StatusCode::BAD_GATEWAY,
+                ErrorResponse { message, code: "sms_delivery_failed", invalid_fields: Vec::new() },
+            )
+        };
+
+        let response = self
+            .http_client
+            .post(&self.config.api_uri)
+            .bearer_auth(&self.config.api_key)
+            .json(&SmsMessage { from: &self.config.sender, to, text })
+            .send()
+            .await
+            .map_err(|err| send_error(format!("Failed to send SMS: {}", err)))?;
+
+        if !response.status().is_success() {
+            return Err(send_error(format!("SMS provider responded with {}", response.status())));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use serde::Serialize;
+use sqlx::MySql;
+
+use crate::{
+    error::{database_error, ErrorResponse},
+    schema::SuggestionSchema,
+    Result,
+};
+
+/// Number of suggestions returned at once
+const SUGGESTION_LIMIT: u32 = 10;
+
+/// Maximum number of characters of the text to complete
+const MAX_PREFIX_LENGTH: usize = 32;
+
+/// Fields of the senior profiles the suggestions come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type, utoipa::ToSchema)]
+pub enum SuggestionKind {
+    Nickname,
+    Major,
+    Tag,
+}
+
+/// Completions of the text typed in the senior search box
+pub struct Suggestion;
+
+impl Suggestion {
+    /// Returns the majors, tags and nicknames of the active seniors starting
+    /// with the prefix, in this order.
+    pub async fn get(prefix: &str, pool: &sqlx::Pool<MySql>) -> Result<Vec<SuggestionSchema>> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_LENGTH {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: format!("q must have 1 to {} characters", MAX_PREFIX_LENGTH),
+                    code: "invalid_query",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        // Every column is compared by its prefix, so the unique indexes are used
+        let pattern = format!("{}%", escape_like(prefix));
+
+        sqlx::query_as_unchecked!(
+            SuggestionSchema,
+            "(SELECT 'Major' AS kind, name AS text FROM majors WHERE parent_id IS NOT NULL AND name LIKE ? ORDER BY name LIMIT ?) UNION ALL (SELECT 'Tag' AS kind, name AS text FROM tags WHERE name LIKE ? AND EXISTS (SELECT 1 FROM senior_tags WHERE senior_tags.tag_id = tags.id) ORDER BY name LIMIT ?) UNION ALL (SELECT 'Nickname' AS kind, nickname AS text FROM senior_users WHERE nickname LIKE ? AND deleted_at IS NULL AND dormant_at IS NULL ORDER BY nickname LIMIT ?) LIMIT ?",
+            pattern,
+            SUGGESTION_LIMIT,
+            pattern,
+            SUGGESTION_LIMIT,
+            pattern,
+            SUGGESTION_LIMIT,
+            SUGGESTION_LIMIT
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+}
+
+/// Escapes the wildcards of `LIKE` so that the text is matched as it is.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
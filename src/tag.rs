@@ -0,0 +1,120 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use sqlx::MySql;
+
+use crate::{
+    error::{database_error, ErrorResponse},
+    filter,
+    schema::TagSchema,
+    user::account::UserId,
+    Result,
+};
+
+/// Number of tags a senior can put on the profile
+const MAX_TAGS_PER_SENIOR: usize = 10;
+
+/// Maximum number of characters in a tag
+const MAX_TAG_LENGTH: usize = 20;
+
+/// Number of tags returned as the popular ones
+const POPULAR_TAG_COUNT: u32 = 20;
+
+#[derive(Debug, sqlx::FromRow)]
+struct TagRow {
+    name: String,
+    senior_count: i64,
+}
+
+/// Keywords the seniors put on their profiles, such as "이직" or "포트폴리오 리뷰"
+pub struct Tag;
+
+impl Tag {
+    pub async fn get_senior_tags(
+        senior_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<String>> {
+        sqlx::query_scalar!(
+            "SELECT tags.name FROM senior_tags JOIN tags ON tags.id = senior_tags.tag_id WHERE senior_tags.senior_user_id = ? ORDER BY tags.name",
+            senior_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+
+    /// Replaces the tags of the senior. The tags nobody used before are
+    /// created.
+    pub async fn set_senior_tags(
+        senior_id: UserId,
+        tags: &[String],
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<String>> {
+        let invalid_tags = |message: String| {
+            Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse { message, code: "invalid_tags", invalid_fields: Vec::new() },
+            ))
+        };
+
+        // The tags are compared without the case like the database does
+        let mut names: Vec<&str> = Vec::new();
+        for tag in tags.iter().map(|tag| tag.trim()) {
+            if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+                return invalid_tags(format!("A tag must have 1 to {} characters", MAX_TAG_LENGTH));
+            }
+            filter::check_tag(tag)?;
+
+            if !names.iter().any(|name| name.to_lowercase() == tag.to_lowercase()) {
+                names.push(tag);
+            }
+        }
+        if names.len() > MAX_TAGS_PER_SENIOR {
+            return invalid_tags(format!("A senior can have at most {} tags", MAX_TAGS_PER_SENIOR));
+        }
+
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        sqlx::query!("DELETE FROM senior_tags WHERE senior_user_id = ?", senior_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+
+        for name in &names {
+            sqlx::query!("INSERT IGNORE INTO tags (name) VALUES (?)", name)
+                .execute(&mut *tx)
+                .await
+                .map_err(database_error)?;
+
+            sqlx::query!(
+                "INSERT INTO senior_tags (senior_user_id, tag_id) SELECT ?, id FROM tags WHERE name = ?",
+                senior_id,
+                name
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+        }
+
+        tx.commit().await.map_err(database_error)?;
+
+        Self::get_senior_tags(senior_id, pool).await
+    }
+
+    /// Returns the tags used by the most active seniors.
+    pub async fn get_popular(pool: &sqlx::Pool<MySql>) -> Result<Vec<TagSchema>> {
+        let tags = sqlx::query_as_unchecked!(
+            TagRow,
+            "SELECT tags.name, COUNT(*) AS senior_count FROM senior_tags JOIN tags ON tags.id = senior_tags.tag_id JOIN senior_users ON senior_users.id = senior_tags.senior_user_id WHERE senior_users.deleted_at IS NULL AND senior_users.dormant_at IS NULL GROUP BY tags.id, tags.name ORDER BY senior_count DESC, tags.name LIMIT ?",
+            POPULAR_TAG_COUNT
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(tags
+            .into_iter()
+            .map(|tag| TagSchema { name: tag.name, senior_count: tag.senior_count as u32 })
+            .collect())
+    }
+}
@@ -10,13 +10,17 @@ use sqlx::{
 };
 
 use crate::{
-    error::ErrorResponse,
-    nickname::{self, KoreanGenerator},
+    crypto::FieldCipher,
+    error::{self, database_error, ErrorResponse},
+    filter,
+    major::Major,
+    redact::Redacted,
     schema::{
-        JsonArray, NormalUserInfoSchema, SeniorRegisterSchema, SeniorSearchResultSchema,
-        SeniorSearchSchema, SeniorUserInfoSchema,
+        self, CursorParams, JsonArray, NormalUpdateSchema, NormalUserExportSchema,
+        NormalUserInfoSchema, Paginated, SeniorRegisterSchema, SeniorSearchSchema,
+        SeniorSearchSort, SeniorUpdateSchema, SeniorUserExportSchema, SeniorUserInfoSchema,
     },
-    user::{picture::get_random_user_picture_url, UserType},
+    user::{nickname as user_nickname, phone, picture::get_random_user_picture_url, UserType},
 };
 use crate::{oauth::OAuthProvider, Result};
 
@@ -24,22 +28,94 @@ use super::OAuthUserData;
 
 pub type UserId = u64;
 
+/// Name of the unique key of `senior_users.email`
+const EMAIL_UNIQUE_KEY: &str = "unique_index";
+
 const PEPPER: &str = "dV9h;TroC@ref}L}\\{_4d31.Fcv?ljN";
 
+/// Hashes the password with a random salt and the pepper.
+fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::new_with_secret(
+        PEPPER.as_bytes(),
+        argon2::Algorithm::default(),
+        argon2::Version::default(),
+        argon2::Params::default(),
+    )
+    .unwrap()
+    .hash_password(password.as_bytes(), &salt)
+    .map_err(|err| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorResponse {
+                message: format!("Error while hashing password: {}", err),
+                code: "password_hash_failed",
+                invalid_fields: Vec::new(),
+            },
+        )
+    })
+    .map(|hash| hash.to_string())
+}
+
+/// Number of seniors recommended to a normal user at once
+const RECOMMENDED_SENIOR_COUNT: u32 = 10;
+
+/// Sum of the profile views of a senior in the last 7 days, which is used to
+/// find the trending seniors
+const POPULARITY_SQL: &str = "(SELECT COALESCE(SUM(view_count), 0) FROM senior_daily_views \
+     WHERE senior_user_id = senior_users.id AND view_date > CURDATE() - INTERVAL 7 DAY)";
+
 #[async_trait]
 pub trait User: Sized {
     fn id(&self) -> UserId;
 
     fn refresh_token(&self) -> Option<&str>;
 
+    /// Returns the current version of the user's tokens. Tokens issued with an
+    /// older version are treated as revoked.
+    fn token_version(&self) -> u32;
+
+    /// Returns the active user. The deleted and the dormant users are only
+    /// found by their login, which reactivates them.
     async fn from_id(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<Self>;
 
+    /// Stores the refresh token issued at login, and records the time of the
+    /// login.
     async fn update_refresh_token(&self, token: &str, pool: &sqlx::Pool<MySql>) -> Result<&Self>;
 
+    /// Marks the user as deleted. The user can cancel the deletion by logging
+    /// in again until the account is purged.
     async fn delete(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId>;
+
+    /// Cancels the deletion of the user.
+    async fn reactivate(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId>;
+
+    /// Permanently deletes the users marked as deleted more than `grace_days`
+    /// days ago, and returns the number of the purged users.
+    async fn purge_deleted(grace_days: u32, pool: &sqlx::Pool<MySql>) -> Result<u64>;
+
+    /// Moves the personal data of the users who have not logged in for
+    /// `inactive_days` days to a separate table, and returns the number of the
+    /// users made dormant.
+    async fn make_dormant(inactive_days: u32, pool: &sqlx::Pool<MySql>) -> Result<u64>;
+
+    /// Brings the personal data of the dormant user back.
+    async fn restore_dormant(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId>;
+
+    /// Changes the nickname of the user. It fails with `409 Conflict` if
+    /// another user already uses the nickname.
+    async fn update_nickname(
+        id: UserId,
+        nickname: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId>;
+
+    /// Clears the stored refresh token and bumps the token version so that
+    /// every token issued to the user so far becomes invalid.
+    async fn revoke_sessions(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId>;
 }
 
-#[derive(Debug, sqlx::FromRow, Serialize, Deserialize, Clone)]
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
 pub struct NormalUser {
     id: UserId,
     oauth_provider: OAuthProvider,
@@ -47,28 +123,61 @@ pub struct NormalUser {
     nickname: String,
     picture: String,
     refresh_token: Option<String>,
+    token_version: u32,
+    last_login_at: DateTime<Utc>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    dormant_at: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Debug for NormalUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NormalUser")
+            .field("id", &self.id)
+            .field("oauth_provider", &self.oauth_provider)
+            .field("oauth_id", &Redacted(&self.oauth_id))
+            .field("nickname", &self.nickname)
+            .field("picture", &self.picture)
+            .field("refresh_token", &Redacted(&self.refresh_token))
+            .field("token_version", &self.token_version)
+            .field("last_login_at", &self.last_login_at)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("deleted_at", &self.deleted_at)
+            .field("dormant_at", &self.dormant_at)
+            .finish()
+    }
 }
 
 impl NormalUser {
     pub async fn register(oauth_user: &OAuthUserData, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
-        let nickname = KoreanGenerator::new(nickname::Naming::Plain).next();
-        let result = sqlx::query!(
-            "INSERT INTO normal_users (oauth_provider, oauth_id, nickname, picture) VALUES (?, ?, ?, ?)",
-            oauth_user.provider,
-            oauth_user.id,
-            nickname,
-            get_random_user_picture_url(UserType::NormalUser)
-        )
-        .execute(pool)
-        .await
-        .map_err(|err| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ErrorResponse { status: "error", message: format!("Database error: {}", err) },
+        // Another user can take the generated nickname before the insertion
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+
+            let nickname = user_nickname::generate_unique(pool).await?;
+            match sqlx::query!(
+                "INSERT INTO normal_users (oauth_provider, oauth_id, nickname, picture) VALUES (?, ?, ?, ?)",
+                oauth_user.provider,
+                oauth_user.id,
+                nickname,
+                get_random_user_picture_url(UserType::NormalUser)
             )
-        })?;
+            .execute(pool)
+            .await
+            {
+                Err(err)
+                    if attempts < user_nickname::MAX_INSERT_ATTEMPTS
+                        && user_nickname::is_duplicate_nickname_error(&err) =>
+                {
+                    continue
+                }
+                result => break result,
+            }
+        }
+        .map_err(database_error)?;
 
         Ok(result.last_insert_id())
     }
@@ -77,24 +186,59 @@ impl NormalUser {
         oauth_user: &OAuthUserData,
         pool: &sqlx::Pool<MySql>,
     ) -> Result<Self> {
-        sqlx::query_as_unchecked!(
+        // The dormant users are found by the OAuth ID kept aside
+        let mut user = sqlx::query_as_unchecked!(
             Self,
-            "SELECT * FROM normal_users WHERE oauth_provider = ? AND oauth_id = ?",
+            "SELECT u.* FROM normal_users u LEFT JOIN dormant_normal_users d ON d.normal_user_id = u.id WHERE u.oauth_provider = ? AND (u.oauth_id = ? OR d.oauth_id = ?)",
             oauth_user.provider(),
+            oauth_user.id(),
             oauth_user.id()
         )
         .fetch_optional(pool)
         .await
-        .map_err(|err| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                ErrorResponse { status: "error", message: format!("Database error: {}", err) },
-            )
-        })?
+        .map_err(database_error)?
         .ok_or((
             StatusCode::BAD_REQUEST,
-            ErrorResponse { status: "fail", message: "Invalid OAuth user data".to_string() },
-        ))
+            ErrorResponse {
+                message: "Invalid OAuth user data".to_string(),
+                code: "invalid_oauth_user",
+                invalid_fields: Vec::new(),
+            },
+        ))?;
+
+        // Logging in again within the grace period cancels the deletion
+        if user.deleted_at.is_some() {
+            Self::reactivate(user.id, pool).await?;
+            user.deleted_at = None;
+        }
+
+        if user.dormant_at.is_some() {
+            Self::restore_dormant(user.id, pool).await?;
+            user = Self::from_id(user.id, pool).await?;
+        }
+
+        Ok(user)
+    }
+
+    /// Updates the provided fields of the profile.
+    pub async fn update(
+        id: UserId,
+        update_data: &NormalUpdateSchema,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        let Some(nickname) = &update_data.nickname else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "No fields to update".to_string(),
+                    code: "no_fields_to_update",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        };
+
+        user_nickname::validate(nickname)?;
+        Self::update_nickname(id, nickname, pool).await
     }
 }
 
@@ -108,55 +252,230 @@ impl User for NormalUser {
         self.refresh_token.as_deref()
     }
 
+    fn token_version(&self) -> u32 {
+        self.token_version
+    }
+
     async fn from_id(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<Self> {
-        sqlx::query_as_unchecked!(Self, "SELECT * FROM normal_users WHERE id = ?", id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse { status: "error", message: format!("Database error: {}", err) },
-                )
-            })?
-            .ok_or((
-                StatusCode::BAD_REQUEST,
-                ErrorResponse { status: "fail", message: "Invalid OAuth user data".to_string() },
-            ))
+        sqlx::query_as_unchecked!(
+            Self,
+            "SELECT * FROM normal_users WHERE id = ? AND deleted_at IS NULL AND dormant_at IS NULL",
+            id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(database_error)?
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Invalid OAuth user data".to_string(),
+                code: "invalid_oauth_user",
+                invalid_fields: Vec::new(),
+            },
+        ))
     }
 
     async fn update_refresh_token(&self, token: &str, pool: &sqlx::Pool<MySql>) -> Result<&Self> {
-        sqlx::query!("UPDATE normal_users SET refresh_token = ? WHERE id = ?", token, self.id)
-            .execute(pool)
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse { status: "error", message: format!("Database error: {}", err) },
-                )
-            })?;
+        sqlx::query!(
+            "UPDATE normal_users SET refresh_token = ?, last_login_at = CURRENT_TIMESTAMP() WHERE id = ?",
+            token,
+            self.id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
 
         Ok(self)
     }
 
     async fn delete(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
-        let result = sqlx::query!("DELETE FROM normal_users WHERE id = ?", id)
-            .execute(pool)
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse { status: "error", message: format!("Database Error: {}", err) },
-                )
-            })?;
+        // Log the user out of every device as well
+        let result = sqlx::query!(
+            "UPDATE normal_users SET deleted_at = CURRENT_TIMESTAMP(), refresh_token = NULL, token_version = token_version + 1 WHERE id = ? AND deleted_at IS NULL",
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        match result.rows_affected() {
+            1.. => Ok(id),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                ErrorResponse {
+                    message: "Cannot find user".to_string(),
+                    code: "user_not_found",
+                    invalid_fields: Vec::new(),
+                },
+            )),
+        }
+    }
+
+    async fn update_nickname(
+        id: UserId,
+        nickname: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        sqlx::query!(
+            "UPDATE normal_users SET nickname = ? WHERE id = ? AND deleted_at IS NULL",
+            nickname,
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(|err| {
+            if user_nickname::is_duplicate_nickname_error(&err) {
+                return (
+                    StatusCode::CONFLICT,
+                    ErrorResponse {
+                        message: "Nickname is already in use".to_string(),
+                        code: "nickname_taken",
+                        invalid_fields: Vec::new(),
+                    },
+                );
+            }
+
+            database_error(err)
+        })?;
+
+        Ok(id)
+    }
+
+    async fn revoke_sessions(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
+        let result = sqlx::query!(
+            "UPDATE normal_users SET refresh_token = NULL, token_version = token_version + 1 WHERE id = ?",
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
 
         match result.rows_affected() {
             1.. => Ok(id),
             _ => Err((
                 StatusCode::NOT_FOUND,
-                ErrorResponse { status: "fail", message: "Cannot find user".to_string() },
+                ErrorResponse {
+                    message: "Cannot find user".to_string(),
+                    code: "user_not_found",
+                    invalid_fields: Vec::new(),
+                },
             )),
         }
     }
+
+    async fn reactivate(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
+        sqlx::query!("UPDATE normal_users SET deleted_at = NULL WHERE id = ?", id)
+            .execute(pool)
+            .await
+            .map_err(database_error)?;
+
+        Ok(id)
+    }
+
+    async fn purge_deleted(grace_days: u32, pool: &sqlx::Pool<MySql>) -> Result<u64> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        // The blocks, the reports, the consents and the settings refer to both
+        // user types, so they cannot be cascaded by foreign keys
+        sqlx::query!(
+            "DELETE FROM user_blocks WHERE (user_type = ? AND user_id IN (SELECT id FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)) OR (blocked_user_type = ? AND blocked_user_id IN (SELECT id FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY))",
+            UserType::NormalUser,
+            grace_days,
+            UserType::NormalUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "DELETE FROM user_reports WHERE (reporter_type = ? AND reporter_id IN (SELECT id FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)) OR (reported_type = ? AND reported_id IN (SELECT id FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY))",
+            UserType::NormalUser,
+            grace_days,
+            UserType::NormalUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "DELETE FROM consents WHERE user_type = ? AND user_id IN (SELECT id FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)",
+            UserType::NormalUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "DELETE FROM user_settings WHERE user_type = ? AND user_id IN (SELECT id FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)",
+            UserType::NormalUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        // The rest of the data of the users is removed by the foreign keys
+        let result = sqlx::query!(
+            "DELETE FROM normal_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY",
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn make_dormant(inactive_days: u32, pool: &sqlx::Pool<MySql>) -> Result<u64> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        sqlx::query!(
+            "INSERT INTO dormant_normal_users (normal_user_id, oauth_id) SELECT id, oauth_id FROM normal_users WHERE last_login_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY AND dormant_at IS NULL AND deleted_at IS NULL",
+            inactive_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        // Keep the unique columns unique with the placeholders, and log the
+        // users out of every device
+        let result = sqlx::query!(
+            "UPDATE normal_users SET oauth_id = CONCAT('dormant:', id), refresh_token = NULL, token_version = token_version + 1, dormant_at = CURRENT_TIMESTAMP() WHERE dormant_at IS NULL AND id IN (SELECT normal_user_id FROM dormant_normal_users)"
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn restore_dormant(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        sqlx::query!(
+            "UPDATE normal_users u JOIN dormant_normal_users d ON d.normal_user_id = u.id SET u.oauth_id = d.oauth_id, u.dormant_at = NULL WHERE u.id = ?",
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!("DELETE FROM dormant_normal_users WHERE normal_user_id = ?", id)
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)?;
+
+        Ok(id)
+    }
 }
 
 impl From<NormalUser> for NormalUserInfoSchema {
@@ -170,13 +489,31 @@ impl From<NormalUser> for NormalUserInfoSchema {
     }
 }
 
-#[derive(Debug, sqlx::FromRow, Serialize, Deserialize, Clone)]
+impl From<NormalUser> for NormalUserExportSchema {
+    fn from(value: NormalUser) -> Self {
+        Self {
+            id: value.id,
+            oauth_provider: value.oauth_provider,
+            oauth_id: value.oauth_id,
+            nickname: value.nickname,
+            picture: value.picture,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone)]
 pub struct SeniorUser {
     id: UserId,
     email: String,
     password: String,
     name: String,
+    /// Phone number encrypted with the [`FieldCipher`]
     phone: String,
+    /// Hash of the phone number to find and compare the encrypted one
+    phone_hash: String,
+    phone_verified: bool,
     nickname: String,
     picture: String,
     major: String,
@@ -184,93 +521,282 @@ pub struct SeniorUser {
     mentoring_price: i32,
     representative_careers: String,
     description: String,
+    view_count: u32,
     refresh_token: Option<String>,
+    token_version: u32,
+    last_login_at: DateTime<Utc>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    dormant_at: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Debug for SeniorUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SeniorUser")
+            .field("id", &self.id)
+            .field("email", &Redacted(&self.email))
+            .field("password", &Redacted(&self.password))
+            .field("name", &Redacted(&self.name))
+            .field("phone", &Redacted(&self.phone))
+            .field("phone_hash", &Redacted(&self.phone_hash))
+            .field("phone_verified", &self.phone_verified)
+            .field("nickname", &self.nickname)
+            .field("picture", &self.picture)
+            .field("major", &self.major)
+            .field("experience_years", &self.experience_years)
+            .field("mentoring_price", &self.mentoring_price)
+            .field("representative_careers", &self.representative_careers)
+            .field("description", &self.description)
+            .field("view_count", &self.view_count)
+            .field("refresh_token", &Redacted(&self.refresh_token))
+            .field("token_version", &self.token_version)
+            .field("last_login_at", &self.last_login_at)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("deleted_at", &self.deleted_at)
+            .field("dormant_at", &self.dormant_at)
+            .finish()
+    }
 }
 
 impl SeniorUser {
+    /// Returns the decrypted phone number.
+    pub fn phone(&self, cipher: &FieldCipher) -> Result<String> {
+        cipher.decrypt(&self.phone)
+    }
+
+    pub fn phone_verified(&self) -> bool {
+        self.phone_verified
+    }
+
+    /// Returns the profile of the senior for the data export, with the phone
+    /// number decrypted.
+    pub fn export(self, cipher: &FieldCipher) -> Result<SeniorUserExportSchema> {
+        Ok(SeniorUserExportSchema {
+            phone: self.phone(cipher)?,
+            id: self.id,
+            email: self.email,
+            name: self.name,
+            phone_verified: self.phone_verified,
+            nickname: self.nickname,
+            picture: self.picture,
+            major: self.major,
+            experience_years: self.experience_years,
+            mentoring_price: self.mentoring_price,
+            representative_careers: JsonArray::from_str(&self.representative_careers)
+                .unwrap_or_default(),
+            description: self.description,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+
+    /// Encrypts the phone numbers stored in plain text before the encryption
+    /// was introduced, which are the ones without a hash, and returns the
+    /// number of them.
+    pub async fn encrypt_plain_phones(
+        cipher: &FieldCipher,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<u64> {
+        let seniors = sqlx::query_as_unchecked!(
+            PlainPhone,
+            "SELECT id, phone FROM senior_users WHERE phone_hash IS NULL"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)?;
+        for senior in &seniors {
+            sqlx::query!(
+                "UPDATE senior_users SET phone = ?, phone_hash = ? WHERE id = ? AND phone_hash IS NULL",
+                cipher.encrypt(&senior.phone)?,
+                cipher.hash(&senior.phone),
+                senior.id
+            )
+            .execute(pool)
+            .await
+            .map_err(database_error)?;
+        }
+
+        let dormant_seniors = sqlx::query_as_unchecked!(
+            PlainPhone,
+            "SELECT senior_user_id AS id, phone FROM dormant_senior_users WHERE phone_hash IS NULL"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)?;
+        for senior in &dormant_seniors {
+            sqlx::query!(
+                "UPDATE dormant_senior_users SET phone = ?, phone_hash = ? WHERE senior_user_id = ? AND phone_hash IS NULL",
+                cipher.encrypt(&senior.phone)?,
+                cipher.hash(&senior.phone),
+                senior.id
+            )
+            .execute(pool)
+            .await
+            .map_err(database_error)?;
+        }
+
+        Ok((seniors.len() + dormant_seniors.len()) as u64)
+    }
+
     pub async fn register(
         register_data: &SeniorRegisterSchema,
+        cipher: &FieldCipher,
         pool: &sqlx::Pool<MySql>,
     ) -> Result<UserId> {
-        if register_data.email.is_empty() || register_data.password.is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                ErrorResponse { status: "fail", message: "email or password is empty".to_string() },
-            ));
-        }
+        schema::validate(register_data)?;
+        filter::check_description(&register_data.description)?;
+        Major::validate(&register_data.major, pool).await?;
+        let phone_number = phone::normalize(&register_data.phone)?;
+        let phone_hash = cipher.hash(&phone_number);
 
-        let salt = SaltString::generate(&mut OsRng);
-        let hashed_password = Argon2::new_with_secret(
-            PEPPER.as_bytes(),
-            argon2::Algorithm::default(),
-            argon2::Version::default(),
-            argon2::Params::default(),
+        // The dormant seniors still own their email and phone number
+        let dormant_count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM dormant_senior_users WHERE email = ? OR phone_hash = ?",
+            register_data.email,
+            phone_hash
         )
-        .unwrap()
-        .hash_password(register_data.password.as_bytes(), &salt)
-        .map_err(|err| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
+        .fetch_one(pool)
+        .await
+        .map_err(database_error)?;
+        if dormant_count > 0 {
+            return Err((
+                StatusCode::CONFLICT,
                 ErrorResponse {
-                    status: "error",
-                    message: format!("Error while hashing password: {}", err),
+                    message: "Email or phone number is already registered".to_string(),
+                    code: "account_exists",
+                    invalid_fields: Vec::new(),
                 },
+            ));
+        }
+
+        let hashed_password = hash_password(&register_data.password)?;
+        let encrypted_phone = cipher.encrypt(&phone_number)?;
+
+        // Another user can take the generated nickname before the insertion
+        let mut attempts = 0;
+        let user = loop {
+            attempts += 1;
+
+            let nickname = user_nickname::generate_unique(pool).await?;
+            match sqlx::query!(
+                "INSERT INTO senior_users (email, password, name, phone, phone_hash, nickname, picture, major, experience_years, mentoring_price, representative_careers, description) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                register_data.email,
+                hashed_password,
+                register_data.name,
+                encrypted_phone,
+                phone_hash,
+                nickname,
+                get_random_user_picture_url(UserType::SeniorUser),
+                register_data.major,
+                register_data.experience_years,
+                register_data.mentoring_price,
+                register_data.representative_careers.to_string(),
+                register_data.description,
             )
-        })
-        .map(|hash| hash.to_string())?;
+            .execute(pool)
+            .await
+            {
+                Err(err)
+                    if attempts < user_nickname::MAX_INSERT_ATTEMPTS
+                        && user_nickname::is_duplicate_nickname_error(&err) =>
+                {
+                    continue
+                }
+                result => break result,
+            }
+        }
+        .map_err(|err| {
+            if error::is_duplicate_entry(&err, EMAIL_UNIQUE_KEY) {
+                return (
+                    StatusCode::CONFLICT,
+                    ErrorResponse {
+                        message: "Email is already registered".to_string(),
+                        code: "email_taken",
+                        invalid_fields: Vec::new(),
+                    },
+                );
+            }
+            if error::is_duplicate_entry(&err, phone::PHONE_UNIQUE_KEY) {
+                return (
+                    StatusCode::CONFLICT,
+                    ErrorResponse {
+                        message: "Phone number is already registered".to_string(),
+                        code: "phone_taken",
+                        invalid_fields: Vec::new(),
+                    },
+                );
+            }
 
-        let nickname = KoreanGenerator::new(nickname::Naming::Plain).next();
-        let user = sqlx::query!(
-            "INSERT INTO senior_users (email, password, name, phone, nickname, picture, major, experience_years, mentoring_price, representative_careers, description) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            register_data.email,
-            hashed_password,
-            register_data.name,
-            register_data.phone,
-            nickname,
-            get_random_user_picture_url(UserType::SeniorUser),
-            register_data.major,
-            register_data.experience_years,
-            register_data.mentoring_price,
-            register_data.representative_careers.to_string(),
-            register_data.description,
-        ).execute(pool).await.map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, ErrorResponse {
-            status: "error",
-            message: format!("Database error: {}", err)
-        }))?;
+            database_error(err)
+        })?;
 
         Ok(user.last_insert_id())
     }
 
-    pub async fn login(email: &str, password: &str, pool: &sqlx::Pool<MySql>) -> Result<Self> {
-        if email.is_empty() || password.is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                ErrorResponse { status: "fail", message: "email or password is empty".to_string() },
-            ));
+    /// Finds the senior who is not deleted by the email.
+    pub async fn find_by_email(email: &str, pool: &sqlx::Pool<MySql>) -> Result<Option<Self>> {
+        sqlx::query_as_unchecked!(
+            Self,
+            "SELECT * FROM senior_users WHERE email = ? AND deleted_at IS NULL",
+            email
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(database_error)
+    }
+
+    /// Replaces the password of the senior and signs out every session, since
+    /// the old password may have been leaked.
+    pub async fn reset_password(
+        id: UserId,
+        password: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        let hashed_password = hash_password(password)?;
+
+        let result = sqlx::query!(
+            "UPDATE senior_users SET password = ?, refresh_token = NULL, token_version = token_version + 1 WHERE id = ? AND deleted_at IS NULL",
+            hashed_password,
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        match result.rows_affected() {
+            1.. => Ok(id),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                ErrorResponse {
+                    message: "Cannot find user".to_string(),
+                    code: "user_not_found",
+                    invalid_fields: Vec::new(),
+                },
+            )),
         }
+    }
 
-        let user =
-            sqlx::query_as_unchecked!(Self, "SELECT * FROM senior_users WHERE email = ?", email)
-                .fetch_optional(pool)
-                .await
-                .map_err(|err| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        ErrorResponse {
-                            status: "error",
-                            message: format!("Database error: {}", err),
-                        },
-                    )
-                })?
-                .ok_or((
-                    StatusCode::BAD_REQUEST,
-                    ErrorResponse {
-                        status: "fail",
-                        message: "Invalid email or password".to_string(),
-                    },
-                ))?;
+    pub async fn login(email: &str, password: &str, pool: &sqlx::Pool<MySql>) -> Result<Self> {
+        // The dormant seniors are found by the email kept aside
+        let mut user = sqlx::query_as_unchecked!(
+            Self,
+            "SELECT u.* FROM senior_users u LEFT JOIN dormant_senior_users d ON d.senior_user_id = u.id WHERE u.email = ? OR d.email = ?",
+            email,
+            email
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(database_error)?
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Invalid email or password".to_string(),
+                code: "invalid_credentials",
+                invalid_fields: Vec::new(),
+            },
+        ))?;
 
         let password_verified = match PasswordHash::new(&user.password) {
             Ok(parsed_hash) => Argon2::new_with_secret(
@@ -288,59 +814,307 @@ impl SeniorUser {
         if !password_verified {
             return Err((
                 StatusCode::BAD_REQUEST,
-                ErrorResponse { status: "fail", message: "Invalid email or password".to_string() },
+                ErrorResponse {
+                    message: "Invalid email or password".to_string(),
+                    code: "invalid_credentials",
+                    invalid_fields: Vec::new(),
+                },
             ));
         }
 
+        // Logging in again within the grace period cancels the deletion
+        if user.deleted_at.is_some() {
+            Self::reactivate(user.id, pool).await?;
+            user.deleted_at = None;
+        }
+
+        if user.dormant_at.is_some() {
+            Self::restore_dormant(user.id, pool).await?;
+            user = Self::from_id(user.id, pool).await?;
+        }
+
         Ok(user)
     }
 
     pub async fn get_all(
         options: SeniorSearchSchema,
+        page: CursorParams,
+        viewer: Option<(UserType, UserId)>,
         pool: &sqlx::Pool<MySql>,
-    ) -> Result<SeniorSearchResultSchema> {
+    ) -> Result<Paginated<SeniorUserInfoSchema>> {
+        schema::validate(&options)?;
+        if let (Some(min_price), Some(max_price)) = (options.min_price, options.max_price) {
+            if min_price > max_price {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    ErrorResponse {
+                        message: "min_price cannot be greater than max_price".to_string(),
+                        code: "invalid_price_range",
+                        invalid_fields: Vec::new(),
+                    },
+                ));
+            }
+        }
+
+        // Column names and the `ORDER BY` clause cannot be bound as parameters, so
+        // they are only built from the fixed list of sorting orders.
+        // (sorting key, comparison of the key, comparison of the id, `ORDER BY`)
+        let (sort_key, key_cmp, id_cmp, order_by) = match options.sort {
+            Some(SeniorSearchSort::PriceAsc) => {
+                ("mentoring_price", ">", ">", "sort_key ASC, id ASC")
+            }
+            Some(SeniorSearchSort::PriceDesc) => {
+                ("mentoring_price", "<", ">", "sort_key DESC, id ASC")
+            }
+            Some(SeniorSearchSort::Experience) => {
+                ("experience_years", "<", ">", "sort_key DESC, id ASC")
+            }
+            Some(SeniorSearchSort::Newest) => {
+                ("UNIX_TIMESTAMP(created_at)", "<", "<", "sort_key DESC, id DESC")
+            }
+            Some(SeniorSearchSort::Popular) => (POPULARITY_SQL, "<", ">", "sort_key DESC, id ASC"),
+            None => ("id", ">", ">", "id ASC"),
+        };
+
+        let mut query = sqlx::QueryBuilder::<MySql>::new(format!(
+            "SELECT *, CAST({sort_key} AS SIGNED) AS sort_key FROM senior_users \
+             WHERE deleted_at IS NULL AND dormant_at IS NULL"
+        ));
+
+        // A category matches the seniors in any of its majors
         if let Some(major) = options.major {
-            let seniors: Vec<SeniorUserInfoSchema> = sqlx::query_as_unchecked!(
-                SeniorUser,
-                "SELECT * FROM senior_users WHERE major = ?",
-                major
-            )
+            query
+                .push(" AND major IN (SELECT name FROM majors WHERE name = ")
+                .push_bind(major.clone())
+                .push(" OR parent_id = (SELECT id FROM majors WHERE name = ")
+                .push_bind(major)
+                .push("))");
+        }
+        if let Some(tag) = options.tag {
+            query
+                .push(
+                    " AND id IN (SELECT senior_user_id FROM senior_tags \
+                     JOIN tags ON tags.id = senior_tags.tag_id WHERE tags.name = ",
+                )
+                .push_bind(tag)
+                .push(")");
+        }
+        if let Some(min_price) = options.min_price {
+            query.push(" AND mentoring_price >= ").push_bind(min_price);
+        }
+        if let Some(max_price) = options.max_price {
+            query.push(" AND mentoring_price <= ").push_bind(max_price);
+        }
+
+        if let Some(viewer) = viewer {
+            push_block_filter(&mut query, viewer);
+        }
+
+        // Continue right after the last item of the previous page
+        if let Some(cursor) = page.cursor.as_deref() {
+            let (key, id) = parse_search_cursor(cursor).ok_or((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "Invalid cursor".to_string(),
+                    code: "invalid_cursor",
+                    invalid_fields: Vec::new(),
+                },
+            ))?;
+
+            query
+                .push(format!(" AND ({sort_key} {key_cmp} "))
+                .push_bind(key)
+                .push(format!(" OR ({sort_key} = "))
+                .push_bind(key)
+                .push(format!(" AND id {id_cmp} "))
+                .push_bind(id)
+                .push("))");
+        }
+
+        // Fetch one more row to know whether the next page exists
+        let limit = page.limit();
+        query.push(" ORDER BY ").push(order_by).push(" LIMIT ").push_bind(limit + 1);
+
+        let mut seniors = query
+            .build_query_as::<SeniorSearchRow>()
             .fetch_all(pool)
             .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
+            .map_err(database_error)?;
+
+        let next_cursor = if seniors.len() > limit as usize {
+            seniors.truncate(limit as usize);
+            seniors.last().map(|row| format!("{}_{}", row.sort_key, row.senior.id))
+        } else {
+            None
+        };
+
+        Ok(Paginated {
+            items: seniors.into_iter().map(|row| row.senior.into()).collect(),
+            next_cursor,
+        })
+    }
+
+    /// Returns the seniors the normal user may like. The seniors sharing the
+    /// majors of the user's favorite seniors come first, then the more popular
+    /// ones. Users without favorites get the most popular seniors.
+    pub async fn get_recommended(
+        user_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<SeniorUserInfoSchema>> {
+        let mut query = sqlx::QueryBuilder::<MySql>::new(
+            "SELECT * FROM senior_users WHERE deleted_at IS NULL AND dormant_at IS NULL \
+             AND id NOT IN (SELECT senior_user_id FROM favorites WHERE normal_user_id = ",
+        );
+        query.push_bind(user_id).push(")");
+
+        push_block_filter(&mut query, (UserType::NormalUser, user_id));
+
+        query
+            .push(
+                " ORDER BY major IN (SELECT senior_users.major FROM favorites \
+                 JOIN senior_users ON senior_users.id = favorites.senior_user_id \
+                 WHERE favorites.normal_user_id = ",
+            )
+            .push_bind(user_id)
+            .push(format!(") DESC, {POPULARITY_SQL} DESC, id ASC LIMIT "))
+            .push_bind(RECOMMENDED_SENIOR_COUNT);
+
+        let seniors =
+            query.build_query_as::<SeniorUser>().fetch_all(pool).await.map_err(database_error)?;
+
+        Ok(seniors.into_iter().map(SeniorUserInfoSchema::from).collect())
+    }
+
+    /// Updates the provided fields of the profile.
+    pub async fn update(
+        id: UserId,
+        update_data: &SeniorUpdateSchema,
+        cipher: &FieldCipher,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        if update_data.nickname.is_none()
+            && update_data.phone.is_none()
+            && update_data.major.is_none()
+            && update_data.experience_years.is_none()
+            && update_data.mentoring_price.is_none()
+            && update_data.representative_careers.is_none()
+            && update_data.description.is_none()
+        {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "No fields to update".to_string(),
+                    code: "no_fields_to_update",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        schema::validate(update_data)?;
+        if let Some(nickname) = &update_data.nickname {
+            user_nickname::validate(nickname)?;
+        }
+        if let Some(description) = &update_data.description {
+            filter::check_description(description)?;
+        }
+        let phone_number = update_data
+            .phone
+            .as_deref()
+            .map(|phone| {
+                let phone_number = phone::normalize(phone)?;
+                Ok((cipher.encrypt(&phone_number)?, cipher.hash(&phone_number)))
+            })
+            .transpose()?;
+        if let Some(major) = &update_data.major {
+            Major::validate(major, pool).await?;
+        }
+
+        let mut query = sqlx::QueryBuilder::<MySql>::new("UPDATE senior_users SET ");
+        let mut fields = query.separated(", ");
+        if let Some(nickname) = &update_data.nickname {
+            fields.push("nickname = ").push_bind_unseparated(nickname);
+        }
+        if let Some((encrypted_phone, phone_hash)) = phone_number {
+            // A new phone number has to be verified again. It is compared
+            // before `phone_hash` is assigned, since MySQL evaluates the
+            // assignments from left to right.
+            fields
+                .push("phone_verified = phone_verified AND phone_hash = ")
+                .push_bind_unseparated(phone_hash.clone());
+            fields.push("phone = ").push_bind_unseparated(encrypted_phone);
+            fields.push("phone_hash = ").push_bind_unseparated(phone_hash);
+        }
+        if let Some(major) = &update_data.major {
+            fields.push("major = ").push_bind_unseparated(major);
+        }
+        if let Some(experience_years) = update_data.experience_years {
+            fields.push("experience_years = ").push_bind_unseparated(experience_years);
+        }
+        if let Some(mentoring_price) = update_data.mentoring_price {
+            fields.push("mentoring_price = ").push_bind_unseparated(mentoring_price);
+        }
+        if let Some(representative_careers) = &update_data.representative_careers {
+            fields
+                .push("representative_careers = ")
+                .push_bind_unseparated(representative_careers.to_string());
+        }
+        if let Some(description) = &update_data.description {
+            fields.push("description = ").push_bind_unseparated(description);
+        }
+
+        query.push(" WHERE id = ").push_bind(id).push(" AND deleted_at IS NULL");
+
+        query.build().execute(pool).await.map_err(|err| {
+            if user_nickname::is_duplicate_nickname_error(&err) {
+                return (
+                    StatusCode::CONFLICT,
                     ErrorResponse {
-                        status: "error",
-                        message: format!("Database error: {:?}", err),
+                        message: "Nickname is already in use".to_string(),
+                        code: "nickname_taken",
+                        invalid_fields: Vec::new(),
                     },
-                )
-            })?
-            .into_iter()
-            .map(|senior| senior.into())
-            .collect();
-
-            return Ok(SeniorSearchResultSchema { seniors });
-        }
-
-        let seniors: Vec<SeniorUserInfoSchema> =
-            sqlx::query_as_unchecked!(Self, "SELECT * FROM senior_users")
-                .fetch_all(pool)
-                .await
-                .map_err(|err| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        ErrorResponse {
-                            status: "error",
-                            message: format!("Database error: {:?}", err),
-                        },
-                    )
-                })?
-                .into_iter()
-                .map(|senior| senior.into())
-                .collect();
-
-        Ok(SeniorSearchResultSchema { seniors })
+                );
+            }
+
+            if error::is_duplicate_entry(&err, phone::PHONE_UNIQUE_KEY) {
+                return (
+                    StatusCode::CONFLICT,
+                    ErrorResponse {
+                        message: "Phone number is already registered".to_string(),
+                        code: "phone_taken",
+                        invalid_fields: Vec::new(),
+                    },
+                );
+            }
+
+            database_error(err)
+        })?;
+
+        Ok(id)
+    }
+
+    /// Counts a view of the senior's profile both in total and per day.
+    pub async fn record_view(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        // Keep `updated_at` as it is, since a view does not update the profile
+        sqlx::query!(
+            "UPDATE senior_users SET view_count = view_count + 1, updated_at = updated_at WHERE id = ?",
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "INSERT INTO senior_daily_views (senior_user_id, view_date, view_count) VALUES (?, CURDATE(), 1) ON DUPLICATE KEY UPDATE view_count = view_count + 1",
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)
     }
 }
 
@@ -354,55 +1128,274 @@ impl User for SeniorUser {
         self.refresh_token.as_deref()
     }
 
+    fn token_version(&self) -> u32 {
+        self.token_version
+    }
+
     async fn from_id(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<Self> {
-        sqlx::query_as_unchecked!(Self, "SELECT * FROM senior_users WHERE id = ?", id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse { status: "error", message: format!("Database error: {}", err) },
-                )
-            })?
-            .ok_or((
-                StatusCode::BAD_REQUEST,
-                ErrorResponse { status: "fail", message: "Invalid senior user id".to_string() },
-            ))
+        sqlx::query_as_unchecked!(
+            Self,
+            "SELECT * FROM senior_users WHERE id = ? AND deleted_at IS NULL AND dormant_at IS NULL",
+            id
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(database_error)?
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Invalid senior user id".to_string(),
+                code: "user_not_found",
+                invalid_fields: Vec::new(),
+            },
+        ))
     }
 
     async fn update_refresh_token(&self, token: &str, pool: &sqlx::Pool<MySql>) -> Result<&Self> {
-        sqlx::query!("UPDATE senior_users SET refresh_token = ? WHERE id = ?", token, self.id)
-            .execute(pool)
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse { status: "error", message: format!("Database error: {}", err) },
-                )
-            })?;
+        sqlx::query!(
+            "UPDATE senior_users SET refresh_token = ?, last_login_at = CURRENT_TIMESTAMP() WHERE id = ?",
+            token,
+            self.id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
 
         Ok(self)
     }
 
     async fn delete(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
-        let result = sqlx::query!("DELETE FROM senior_users WHERE id = ?", id)
-            .execute(pool)
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    ErrorResponse { status: "error", message: format!("Database Error: {}", err) },
-                )
-            })?;
+        // Log the user out of every device as well
+        let result = sqlx::query!(
+            "UPDATE senior_users SET deleted_at = CURRENT_TIMESTAMP(), refresh_token = NULL, token_version = token_version + 1 WHERE id = ? AND deleted_at IS NULL",
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        match result.rows_affected() {
+            1.. => Ok(id),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                ErrorResponse {
+                    message: "Cannot find user".to_string(),
+                    code: "user_not_found",
+                    invalid_fields: Vec::new(),
+                },
+            )),
+        }
+    }
+
+    async fn update_nickname(
+        id: UserId,
+        nickname: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        sqlx::query!(
+            "UPDATE senior_users SET nickname = ? WHERE id = ? AND deleted_at IS NULL",
+            nickname,
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(|err| {
+            if user_nickname::is_duplicate_nickname_error(&err) {
+                return (
+                    StatusCode::CONFLICT,
+                    ErrorResponse {
+                        message: "Nickname is already in use".to_string(),
+                        code: "nickname_taken",
+                        invalid_fields: Vec::new(),
+                    },
+                );
+            }
+
+            database_error(err)
+        })?;
+
+        Ok(id)
+    }
+
+    async fn revoke_sessions(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
+        let result = sqlx::query!(
+            "UPDATE senior_users SET refresh_token = NULL, token_version = token_version + 1 WHERE id = ?",
+            id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
 
         match result.rows_affected() {
             1.. => Ok(id),
             _ => Err((
                 StatusCode::NOT_FOUND,
-                ErrorResponse { status: "fail", message: "Cannot find user".to_string() },
+                ErrorResponse {
+                    message: "Cannot find user".to_string(),
+                    code: "user_not_found",
+                    invalid_fields: Vec::new(),
+                },
             )),
         }
     }
+
+    async fn reactivate(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
+        sqlx::query!("UPDATE senior_users SET deleted_at = NULL WHERE id = ?", id)
+            .execute(pool)
+            .await
+            .map_err(database_error)?;
+
+        Ok(id)
+    }
+
+    async fn purge_deleted(grace_days: u32, pool: &sqlx::Pool<MySql>) -> Result<u64> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        // The blocks, the reports, the consents and the settings refer to both
+        // user types, so they cannot be cascaded by foreign keys
+        sqlx::query!(
+            "DELETE FROM user_blocks WHERE (user_type = ? AND user_id IN (SELECT id FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)) OR (blocked_user_type = ? AND blocked_user_id IN (SELECT id FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY))",
+            UserType::SeniorUser,
+            grace_days,
+            UserType::SeniorUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "DELETE FROM user_reports WHERE (reporter_type = ? AND reporter_id IN (SELECT id FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)) OR (reported_type = ? AND reported_id IN (SELECT id FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY))",
+            UserType::SeniorUser,
+            grace_days,
+            UserType::SeniorUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "DELETE FROM consents WHERE user_type = ? AND user_id IN (SELECT id FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)",
+            UserType::SeniorUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!(
+            "DELETE FROM user_settings WHERE user_type = ? AND user_id IN (SELECT id FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY)",
+            UserType::SeniorUser,
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        // The rest of the data of the users is removed by the foreign keys
+        let result = sqlx::query!(
+            "DELETE FROM senior_users WHERE deleted_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY",
+            grace_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn make_dormant(inactive_days: u32, pool: &sqlx::Pool<MySql>) -> Result<u64> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        sqlx::query!(
+            "INSERT INTO dormant_senior_users (senior_user_id, email, name, phone, phone_hash) SELECT id, email, name, phone, phone_hash FROM senior_users WHERE last_login_at < CURRENT_TIMESTAMP() - INTERVAL ? DAY AND dormant_at IS NULL AND deleted_at IS NULL",
+            inactive_days
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        // Keep the unique columns unique with the placeholders, and log the
+        // users out of every device
+        let result = sqlx::query!(
+            "UPDATE senior_users SET email = CONCAT('dormant:', id), name = '', phone = CONCAT('dormant:', id), phone_hash = CONCAT('dormant:', id), refresh_token = NULL, token_version = token_version + 1, dormant_at = CURRENT_TIMESTAMP() WHERE dormant_at IS NULL AND id IN (SELECT senior_user_id FROM dormant_senior_users)"
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn restore_dormant(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<UserId> {
+        let mut tx = pool.begin().await.map_err(database_error)?;
+
+        sqlx::query!(
+            "UPDATE senior_users u JOIN dormant_senior_users d ON d.senior_user_id = u.id SET u.email = d.email, u.name = d.name, u.phone = d.phone, u.phone_hash = d.phone_hash, u.dormant_at = NULL WHERE u.id = ?",
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+        sqlx::query!("DELETE FROM dormant_senior_users WHERE senior_user_id = ?", id)
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+
+        tx.commit().await.map_err(database_error)?;
+
+        Ok(id)
+    }
+}
+
+/// A phone number stored before the encryption
+struct PlainPhone {
+    id: UserId,
+    phone: String,
+}
+
+/// A row of the senior search with the value of the sorting key
+#[derive(Debug, sqlx::FromRow)]
+struct SeniorSearchRow {
+    #[sqlx(flatten)]
+    senior: SeniorUser,
+    sort_key: i64,
+}
+
+/// Hides the seniors blocked by the viewer and the seniors who blocked the
+/// viewer from the query on `senior_users`.
+fn push_block_filter(query: &mut sqlx::QueryBuilder<'_, MySql>, viewer: (UserType, UserId)) {
+    let (user_type, user_id) = viewer;
+
+    query
+        .push(" AND id NOT IN (SELECT blocked_user_id FROM user_blocks WHERE user_type = ")
+        .push_bind(user_type)
+        .push(" AND user_id = ")
+        .push_bind(user_id)
+        .push(" AND blocked_user_type = ")
+        .push_bind(UserType::SeniorUser)
+        .push(")")
+        .push(" AND id NOT IN (SELECT user_id FROM user_blocks WHERE user_type = ")
+        .push_bind(UserType::SeniorUser)
+        .push(" AND blocked_user_type = ")
+        .push_bind(user_type)
+        .push(" AND blocked_user_id = ")
+        .push_bind(user_id)
+        .push(")");
+}
+
+/// Parses the cursor of the senior search made of the sorting key and the id of
+/// the last senior in the previous page. (ex. "30000_12")
+fn parse_search_cursor(cursor: &str) -> Option<(i64, UserId)> {
+    let (key, id) = cursor.split_once('_')?;
+
+    Some((key.parse().ok()?, id.parse().ok()?))
 }
 
 impl From<SeniorUser> for SeniorUserInfoSchema {
@@ -417,6 +1410,8 @@ impl From<SeniorUser> for SeniorUserInfoSchema {
             representative_careers: JsonArray::from_str(&value.representative_careers)
                 .unwrap_or_default(),
             description: value.description,
+            view_count: value.view_count,
+            is_favorite: None,
         }
     }
 }
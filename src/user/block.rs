@@ -0,0 +1,164 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use sqlx::MySql;
+
+use crate::{
+    error::{database_error, ErrorResponse},
+    schema::{BlockExportSchema, ReportExportSchema},
+    Result,
+};
+
+use super::{account::UserId, UserType};
+
+/// Maximum length of the reason of a report
+pub const MAX_REPORT_REASON_LENGTH: usize = 1024;
+
+/// Users hidden from another user
+pub struct Block;
+
+impl Block {
+    pub async fn add(
+        user: (UserType, UserId),
+        blocked_user: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        if user == blocked_user {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "You cannot block yourself".to_string(),
+                    code: "self_block",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        // Blocking the same user twice is not an error
+        sqlx::query!(
+            "INSERT IGNORE INTO user_blocks (user_type, user_id, blocked_user_type, blocked_user_id) VALUES (?, ?, ?, ?)",
+            user.0,
+            user.1,
+            blocked_user.0,
+            blocked_user.1
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(blocked_user.1)
+    }
+
+    pub async fn remove(
+        user: (UserType, UserId),
+        blocked_user: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        let result = sqlx::query!(
+            "DELETE FROM user_blocks WHERE user_type = ? AND user_id = ? AND blocked_user_type = ? AND blocked_user_id = ?",
+            user.0,
+            user.1,
+            blocked_user.0,
+            blocked_user.1
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        match result.rows_affected() {
+            1.. => Ok(blocked_user.1),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                ErrorResponse {
+                    message: "Cannot find blocked user".to_string(),
+                    code: "block_not_found",
+                    invalid_fields: Vec::new(),
+                },
+            )),
+        }
+    }
+
+    /// Returns every user blocked by the user for the data export.
+    pub async fn export(
+        user: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<BlockExportSchema>> {
+        sqlx::query_as_unchecked!(
+            BlockExportSchema,
+            "SELECT blocked_user_type, blocked_user_id, created_at FROM user_blocks WHERE user_type = ? AND user_id = ? ORDER BY id",
+            user.0,
+            user.1
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+}
+
+/// Reports filed by users to be reviewed by the administrators
+pub struct Report;
+
+impl Report {
+    pub async fn create(
+        reporter: (UserType, UserId),
+        reported_user: (UserType, UserId),
+        reason: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<u64> {
+        let reason = reason.trim();
+        if reason.is_empty() || reason.chars().count() > MAX_REPORT_REASON_LENGTH {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: format!(
+                        "The reason must be between 1 and {} characters",
+                        MAX_REPORT_REASON_LENGTH
+                    ),
+                    code: "invalid_report_reason",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        if reporter == reported_user {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "You cannot report yourself".to_string(),
+                    code: "self_report",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        let result = sqlx::query!(
+            "INSERT INTO user_reports (reporter_type, reporter_id, reported_type, reported_id, reason) VALUES (?, ?, ?, ?, ?)",
+            reporter.0,
+            reporter.1,
+            reported_user.0,
+            reported_user.1,
+            reason
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(result.last_insert_id())
+    }
+
+    /// Returns every report filed by the user for the data export.
+    pub async fn export(
+        reporter: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<ReportExportSchema>> {
+        sqlx::query_as_unchecked!(
+            ReportExportSchema,
+            "SELECT reported_type, reported_id, reason, created_at FROM user_reports WHERE reporter_type = ? AND reporter_id = ? ORDER BY id",
+            reporter.0,
+            reporter.1
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::str::FromStr;
+
+use axum::{async_trait, extract::multipart, http::StatusCode};
+use axum_typed_multipart::TypedMultipartError;
+use serde::{Deserialize, Serialize};
+use sqlx::MySql;
+
+use crate::{
+    error::{database_error, ErrorResponse},
+    schema::{ConsentExportSchema, PendingConsentSchema},
+    Result,
+};
+
+use super::{account::UserId, UserType};
+
+/// Current version of the terms of service
+const TERMS_OF_SERVICE_VERSION: u32 = 1;
+/// Current version of the privacy policy
+const PRIVACY_POLICY_VERSION: u32 = 1;
+/// Current version of the consent to receive marketing messages
+const MARKETING_VERSION: u32 = 1;
+
+/// Documents the users have to answer to use the service
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentType {
+    TermsOfService,
+    PrivacyPolicy,
+    /// Optional consent to receive marketing messages
+    Marketing,
+}
+
+impl ConsentType {
+    pub const ALL: [ConsentType; 3] =
+        [ConsentType::TermsOfService, ConsentType::PrivacyPolicy, ConsentType::Marketing];
+
+    pub fn current_version(self) -> u32 {
+        match self {
+            ConsentType::TermsOfService => TERMS_OF_SERVICE_VERSION,
+            ConsentType::PrivacyPolicy => PRIVACY_POLICY_VERSION,
+            ConsentType::Marketing => MARKETING_VERSION,
+        }
+    }
+
+    /// Returns whether the users must agree to the document to use the service.
+    pub fn is_required(self) -> bool {
+        !matches!(self, ConsentType::Marketing)
+    }
+}
+
+impl FromStr for ConsentType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "terms_of_service" => Ok(Self::TermsOfService),
+            "privacy_policy" => Ok(Self::PrivacyPolicy),
+            "marketing" => Ok(Self::Marketing),
+            _ => Err("Invalid consent type string".to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl axum_typed_multipart::TryFromField for ConsentType {
+    async fn try_from_field(
+        field: multipart::Field<'_>,
+    ) -> std::result::Result<Self, TypedMultipartError> {
+        let field_name = field.name().unwrap_or("{unknown}").to_string();
+        let field_text = field.text().await?;
+
+        Ok(ConsentType::from_str(&field_text).map_err(|_| TypedMultipartError::WrongFieldType {
+            field_name,
+            wanted_type: "consent type".to_string(),
+        })?)
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct AnsweredConsent {
+    consent_type: ConsentType,
+    version: u32,
+}
+
+/// Answers of the users to the versions of the terms and policies
+pub struct Consent;
+
+impl Consent {
+    /// Returns the current documents the user has not answered yet.
+    pub async fn get_pending(
+        user: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<PendingConsentSchema>> {
+        let answered = sqlx::query_as_unchecked!(
+            AnsweredConsent,
+            "SELECT consent_type, version FROM consents WHERE user_type = ? AND user_id = ?",
+            user.0,
+            user.1
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(ConsentType::ALL
+            .into_iter()
+            .filter(|consent_type| {
+                !answered.iter().any(|consent| {
+                    consent.consent_type == *consent_type
+                        && consent.version == consent_type.current_version()
+                })
+            })
+            .map(|consent_type| PendingConsentSchema {
+                consent_type,
+                version: consent_type.current_version(),
+                required: consent_type.is_required(),
+            })
+            .collect())
+    }
+
+    /// Records the answer of the user to the current version of the document.
+    pub async fn answer(
+        user: (UserType, UserId),
+        consent_type: ConsentType,
+        version: u32,
+        agreed: bool,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        if version != consent_type.current_version() {
+            return Err((
+                StatusCode::CONFLICT,
+                ErrorResponse {
+                    message: format!(
+                        "The current version of the document is {}",
+                        consent_type.current_version()
+                    ),
+                    code: "consent_version_outdated",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+        if consent_type.is_required() && !agreed {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "You must agree to the document to use the service".to_string(),
+                    code: "consent_required",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        // The users can change the optional answers later
+        sqlx::query!(
+            "INSERT INTO consents (user_type, user_id, consent_type, version, agreed) VALUES (?, ?, ?, ?, ?) ON DUPLICATE KEY UPDATE agreed = VALUES(agreed), created_at = CURRENT_TIMESTAMP()",
+            user.0,
+            user.1,
+            consent_type,
+            version,
+            agreed
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(())
+    }
+
+    pub async fn export(
+        user: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<ConsentExportSchema>> {
+        sqlx::query_as_unchecked!(
+            ConsentExportSchema,
+            "SELECT consent_type, version, agreed, created_at FROM consents WHERE user_type = ? AND user_id = ? ORDER BY id",
+            user.0,
+            user.1
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+}
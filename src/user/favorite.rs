@@ -0,0 +1,178 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::collections::HashSet;
+
+use axum::http::StatusCode;
+use sqlx::MySql;
+
+use crate::{
+    error::{database_error, ErrorResponse},
+    schema::{CursorParams, FavoriteExportSchema, Paginated, SeniorUserInfoSchema},
+    Result,
+};
+
+use super::account::{SeniorUser, UserId};
+
+/// Seniors saved by normal users to consider them later
+pub struct Favorite;
+
+#[derive(Debug, sqlx::FromRow)]
+struct FavoriteSenior {
+    favorite_id: u64,
+    #[sqlx(flatten)]
+    senior: SeniorUser,
+}
+
+impl Favorite {
+    pub async fn add(
+        user_id: UserId,
+        senior_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        // Saving the same senior twice is not an error
+        sqlx::query!(
+            "INSERT IGNORE INTO favorites (normal_user_id, senior_user_id) VALUES (?, ?)",
+            user_id,
+            senior_id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(senior_id)
+    }
+
+    pub async fn remove(
+        user_id: UserId,
+        senior_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        let result = sqlx::query!(
+            "DELETE FROM favorites WHERE normal_user_id = ? AND senior_user_id = ?",
+            user_id,
+            senior_id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        match result.rows_affected() {
+            1.. => Ok(senior_id),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                ErrorResponse {
+                    message: "Cannot find favorite".to_string(),
+                    code: "favorite_not_found",
+                    invalid_fields: Vec::new(),
+                },
+            )),
+        }
+    }
+
+    /// Returns the seniors saved by the user, the most recently saved first.
+    pub async fn get_all(
+        user_id: UserId,
+        page: CursorParams,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Paginated<SeniorUserInfoSchema>> {
+        let mut query = sqlx::QueryBuilder::<MySql>::new(
+            "SELECT favorites.id AS favorite_id, senior_users.* FROM favorites \
+             JOIN senior_users ON senior_users.id = favorites.senior_user_id \
+             WHERE senior_users.deleted_at IS NULL AND favorites.normal_user_id = ",
+        );
+        query.push_bind(user_id);
+
+        // The cursor is the id of the last favorite in the previous page
+        if let Some(cursor) = page.cursor.as_deref() {
+            let favorite_id: u64 = cursor.parse().map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    ErrorResponse {
+                        message: "Invalid cursor".to_string(),
+                        code: "invalid_cursor",
+                        invalid_fields: Vec::new(),
+                    },
+                )
+            })?;
+            query.push(" AND favorites.id < ").push_bind(favorite_id);
+        }
+
+        // Fetch one more row to know whether the next page exists
+        let limit = page.limit();
+        query.push(" ORDER BY favorites.id DESC LIMIT ").push_bind(limit + 1);
+
+        let mut favorites = query
+            .build_query_as::<FavoriteSenior>()
+            .fetch_all(pool)
+            .await
+            .map_err(database_error)?;
+
+        let next_cursor = if favorites.len() > limit as usize {
+            favorites.truncate(limit as usize);
+            favorites.last().map(|favorite| favorite.favorite_id.to_string())
+        } else {
+            None
+        };
+
+        Ok(Paginated {
+            items: favorites
+                .into_iter()
+                .map(|favorite| SeniorUserInfoSchema {
+                    is_favorite: Some(true),
+                    ..favorite.senior.into()
+                })
+                .collect(),
+            next_cursor,
+        })
+    }
+
+    /// Fills `is_favorite` of the seniors with whether the user saved them.
+    pub async fn mark_seniors(
+        user_id: UserId,
+        seniors: &mut [SeniorUserInfoSchema],
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        if seniors.is_empty() {
+            return Ok(());
+        }
+
+        let mut query = sqlx::QueryBuilder::<MySql>::new(
+            "SELECT senior_user_id FROM favorites WHERE normal_user_id = ",
+        );
+        query.push_bind(user_id).push(" AND senior_user_id IN (");
+        let mut senior_ids = query.separated(", ");
+        for senior in seniors.iter() {
+            senior_ids.push_bind(senior.id);
+        }
+        senior_ids.push_unseparated(")");
+
+        let favorites: HashSet<UserId> = query
+            .build_query_scalar::<UserId>()
+            .fetch_all(pool)
+            .await
+            .map_err(database_error)?
+            .into_iter()
+            .collect();
+
+        for senior in seniors.iter_mut() {
+            senior.is_favorite = Some(favorites.contains(&senior.id));
+        }
+
+        Ok(())
+    }
+
+    /// Returns every favorite of the user for the data export.
+    pub async fn export(
+        user_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<FavoriteExportSchema>> {
+        sqlx::query_as_unchecked!(
+            FavoriteExportSchema,
+            "SELECT senior_user_id, created_at FROM favorites WHERE normal_user_id = ? ORDER BY id",
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+}
@@ -6,12 +6,22 @@ use axum::{async_trait, extract::multipart};
 use axum_typed_multipart::TypedMultipartError;
 use serde::{Deserialize, Serialize};
 
-use crate::oauth::OAuthProvider;
+use crate::{oauth::OAuthProvider, redact::Redacted};
 
 pub mod account;
+pub mod block;
+pub mod consent;
+pub mod favorite;
+pub mod nickname;
+pub mod phone;
 pub mod picture;
+pub mod recently_viewed;
+pub mod recovery;
+pub mod settings;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
 pub enum UserType {
     NormalUser,
     SeniorUser,
@@ -48,12 +58,20 @@ impl axum_typed_multipart::TryFromField for UserType {
     }
 }
 
-#[derive(Debug)]
 pub struct OAuthUserData {
     provider: OAuthProvider,
     id: String,
 }
 
+impl std::fmt::Debug for OAuthUserData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthUserData")
+            .field("provider", &self.provider)
+            .field("id", &Redacted(&self.id))
+            .finish()
+    }
+}
+
 impl OAuthUserData {
     pub fn new(provider: OAuthProvider, id: &str) -> Self {
         Self { provider, id: id.to_string() }
@@ -0,0 +1,118 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use sqlx::MySql;
+
+use crate::{
+    error::{self, database_error, ErrorResponse},
+    filter,
+    nickname::{KoreanGenerator, Naming},
+    Result,
+};
+
+use super::account::{User, UserId};
+
+/// Maximum length of a nickname in characters
+pub const MAX_NICKNAME_LENGTH: usize = 32;
+
+/// Number of attempts to insert a user when another user takes the generated
+/// nickname first
+pub const MAX_INSERT_ATTEMPTS: usize = 3;
+
+/// Number of attempts to generate a nickname nobody uses
+const MAX_GENERATION_ATTEMPTS: usize = 10;
+
+/// Name of the unique keys of the nickname columns
+const NICKNAME_UNIQUE_KEY: &str = "nickname_unique";
+
+/// Returns an error if the nickname is empty, too long, or contains a word
+/// that is not allowed.
+pub fn validate(nickname: &str) -> Result<()> {
+    let length = nickname.chars().count();
+    if nickname.trim().is_empty() || length > MAX_NICKNAME_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: format!(
+                    "Nickname must be between 1 and {} characters",
+                    MAX_NICKNAME_LENGTH
+                ),
+                code: "invalid_nickname",
+                invalid_fields: Vec::new(),
+            },
+        ));
+    }
+
+    filter::check_nickname(nickname)
+}
+
+/// Returns whether no user uses the nickname. The users in the grace period of
+/// the deletion keep their nicknames.
+pub async fn is_available(nickname: &str, pool: &sqlx::Pool<MySql>) -> Result<bool> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM (SELECT id FROM normal_users WHERE nickname = ? UNION ALL SELECT id FROM senior_users WHERE nickname = ?) AS users",
+        nickname,
+        nickname
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(database_error)?;
+
+    Ok(count == 0)
+}
+
+/// Generates a random nickname nobody uses.
+pub async fn generate_unique(pool: &sqlx::Pool<MySql>) -> Result<String> {
+    for attempt in 0..MAX_GENERATION_ATTEMPTS {
+        // Plain names are preferred, but they can run out
+        let naming =
+            if attempt < MAX_GENERATION_ATTEMPTS / 2 { Naming::Plain } else { Naming::Numbered };
+
+        // The generator holds a `ThreadRng`, which must be dropped before the
+        // await to keep the future `Send`
+        let nickname = {
+            // The generator does not produce a name for every draw
+            let mut generator = KoreanGenerator::new(naming);
+            loop {
+                if let Some(nickname) = generator.next() {
+                    break nickname;
+                }
+            }
+        };
+
+        if is_available(&nickname, pool).await? {
+            return Ok(nickname);
+        }
+    }
+
+    Err((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorResponse {
+            message: "Failed to generate a nickname".to_string(),
+            code: "nickname_generation_failed",
+            invalid_fields: Vec::new(),
+        },
+    ))
+}
+
+/// Replaces the nickname of the user with a new random one, and returns it.
+pub async fn regenerate<U: User>(id: UserId, pool: &sqlx::Pool<MySql>) -> Result<String> {
+    // Another user can take the generated nickname before the update
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+
+        let nickname = generate_unique(pool).await?;
+        match U::update_nickname(id, &nickname, pool).await {
+            Ok(_) => return Ok(nickname),
+            Err((StatusCode::CONFLICT, _)) if attempts < MAX_INSERT_ATTEMPTS => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Returns whether the error is caused by inserting a nickname that is already
+/// in use.
+pub fn is_duplicate_nickname_error(err: &sqlx::Error) -> bool {
+    error::is_duplicate_entry(err, NICKNAME_UNIQUE_KEY)
+}
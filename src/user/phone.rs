@@ -0,0 +1,369 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use axum::http::StatusCode;
+use rand::Rng;
+use ring::constant_time;
+use sqlx::MySql;
+
+use crate::{
+    crypto::FieldCipher,
+    error::{database_error, ErrorResponse},
+    sms::SmsClient,
+    Result,
+};
+
+use super::{
+    account::UserId,
+    settings::{Locale, UserSettings},
+    UserType,
+};
+
+/// Country calling code of South Korea
+const COUNTRY_CODE: &str = "+82";
+
+/// Mobile carrier prefixes of South Korea, without the leading zero
+const MOBILE_PREFIXES: &[&str] = &["10", "11", "16", "17", "18", "19"];
+
+/// Name of the unique key of `senior_users.phone_hash`
+pub const PHONE_UNIQUE_KEY: &str = "phone_unique";
+
+/// Seconds until a verification code expires
+const VERIFICATION_CODE_TTL: u32 = 300;
+
+/// Seconds the user has to wait before requesting another verification code
+const VERIFICATION_RESEND_INTERVAL: u32 = 60;
+
+/// Number of wrong codes the user can enter before requesting a new one
+const MAX_VERIFICATION_ATTEMPTS: u32 = 5;
+
+/// Parses a Korean mobile phone number, such as `010-1234-5678` or
+/// `+82 10 1234 5678`, and returns it in the E.164 format
+/// (`+821012345678`).
+pub fn normalize(phone: &str) -> Result<String> {
+    let invalid_phone = || {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorResponse {
+                message: "Invalid phone number".to_string(),
+                code: "invalid_phone",
+                invalid_fields: Vec::new(),
+            },
+        )
+    };
+
+    // Remove the separators people usually write in phone numbers
+    let phone: String =
+        phone.chars().filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')')).collect();
+
+    // Take the national significant number, which is written without the
+    // trunk prefix `0`. The country code is also written without the `+`
+    // sometimes, which cannot be mistaken for a national number since those
+    // start with `0`.
+    let international_number =
+        phone.strip_prefix(COUNTRY_CODE).or_else(|| phone.strip_prefix(&COUNTRY_CODE[1..]));
+    let national_number = match international_number {
+        Some(number) => number.strip_prefix('0').unwrap_or(number),
+        None => phone.strip_prefix('0').ok_or_else(invalid_phone)?,
+    };
+
+    if !national_number.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_phone());
+    }
+
+    let (prefix, subscriber_number) = national_number.split_at(national_number.len().min(2));
+    if !MOBILE_PREFIXES.contains(&prefix) {
+        return Err(invalid_phone());
+    }
+
+    // `010` numbers always have 8 digits after the prefix, while the old
+    // carrier prefixes also have 7 digit ones
+    let valid_length = match prefix {
+        "10" => subscriber_number.len() == 8,
+        _ => matches!(subscriber_number.len(), 7 | 8),
+    };
+    if !valid_length {
+        return Err(invalid_phone());
+    }
+
+    Ok(format!("{}{}", COUNTRY_CODE, national_number))
+}
+
+/// Purposes of the codes sent to the phone numbers. A senior can have a
+/// pending code for each purpose at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+pub enum VerificationPurpose {
+    /// Proving the senior owns the phone number
+    PhoneVerification,
+    /// Resetting the password of a senior who cannot use the email
+    AccountRecovery,
+}
+
+impl VerificationPurpose {
+    fn message(self, locale: Locale, code: &str) -> String {
+        match (self, locale) {
+            (VerificationPurpose::PhoneVerification, Locale::Ko) => {
+                format!("[리스펙] 인증번호는 {} 입니다.", code)
+            }
+            (VerificationPurpose::PhoneVerification, Locale::En) => {
+                format!("[Respec] Your verification code is {}.", code)
+            }
+            (VerificationPurpose::AccountRecovery, Locale::Ko) => {
+                format!("[리스펙] 계정 복구 인증번호는 {} 입니다. 타인에게 알려주지 마세요.", code)
+            }
+            (VerificationPurpose::AccountRecovery, Locale::En) => {
+                format!(
+                    "[Respec] Your account recovery code is {}. Do not share it with anyone.",
+                    code
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct PendingVerification {
+    phone_hash: String,
+    code: String,
+    attempts: u32,
+}
+
+/// Verification codes sent to the phone numbers of the seniors by SMS
+pub struct PhoneVerification;
+
+impl PhoneVerification {
+    /// Sends a new code for the purpose to the phone number of the senior. The
+    /// previous code of the senior for the purpose is no longer accepted.
+    pub async fn send(
+        senior_user_id: UserId,
+        purpose: VerificationPurpose,
+        phone: &str,
+        cipher: &FieldCipher,
+        sms_client: &SmsClient,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        // Every message costs money, so do not let the users send them in a row
+        let recent_count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM phone_verifications WHERE senior_user_id = ? AND purpose = ? AND created_at > CURRENT_TIMESTAMP() - INTERVAL ? SECOND",
+            senior_user_id,
+            purpose,
+            VERIFICATION_RESEND_INTERVAL
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(database_error)?;
+        if recent_count > 0 {
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                ErrorResponse {
+                    message: format!(
+                        "Wait {} seconds before requesting another code",
+                        VERIFICATION_RESEND_INTERVAL
+                    ),
+                    code: "verification_cooldown",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+
+        sqlx::query!(
+            "INSERT INTO phone_verifications (senior_user_id, purpose, phone_hash, code, expires_at) VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP() + INTERVAL ? SECOND) ON DUPLICATE KEY UPDATE phone_hash = VALUES(phone_hash), code = VALUES(code), attempts = 0, expires_at = VALUES(expires_at), created_at = CURRENT_TIMESTAMP()",
+            senior_user_id,
+            purpose,
+            cipher.hash(phone),
+            code,
+            VERIFICATION_CODE_TTL
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        let locale = UserSettings::locale((UserType::SeniorUser, senior_user_id), pool).await?;
+        sms_client.send(phone, &purpose.message(locale, &code)).await
+    }
+
+    /// Checks the code against the last one sent to the senior for the
+    /// purpose. The code is used up on success, and the hash of the phone
+    /// number it was sent to is returned.
+    pub async fn verify(
+        senior_user_id: UserId,
+        purpose: VerificationPurpose,
+        code: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<String> {
+        // Use up an attempt before comparing, in a single statement, so that
+        // concurrent requests cannot try more codes than allowed
+        let result = sqlx::query!(
+            "UPDATE phone_verifications SET attempts = attempts + 1 WHERE senior_user_id = ? AND purpose = ? AND attempts < ? AND expires_at > CURRENT_TIMESTAMP()",
+            senior_user_id,
+            purpose,
+            MAX_VERIFICATION_ATTEMPTS
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+        if result.rows_affected() == 0 {
+            let pending = sqlx::query_scalar!(
+                "SELECT COUNT(*) FROM phone_verifications WHERE senior_user_id = ? AND purpose = ? AND expires_at > CURRENT_TIMESTAMP()",
+                senior_user_id,
+                purpose
+            )
+            .fetch_one(pool)
+            .await
+            .map_err(database_error)?;
+
+            return Err(if pending > 0 { attempts_exceeded() } else { verification_expired() });
+        }
+
+        let verification = sqlx::query_as_unchecked!(
+            PendingVerification,
+            "SELECT phone_hash, code, attempts FROM phone_verifications WHERE senior_user_id = ? AND purpose = ?",
+            senior_user_id,
+            purpose
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(verification_expired)?;
+
+        // Compare in constant time not to leak how much of the code is right
+        if constant_time::verify_slices_are_equal(verification.code.as_bytes(), code.as_bytes())
+            .is_err()
+        {
+            // The code is thrown away after too many wrong codes, so that it
+            // cannot be guessed any further
+            if verification.attempts >= MAX_VERIFICATION_ATTEMPTS {
+                sqlx::query!(
+                    "DELETE FROM phone_verifications WHERE senior_user_id = ? AND purpose = ? AND code = ?",
+                    senior_user_id,
+                    purpose,
+                    verification.code
+                )
+                .execute(pool)
+                .await
+                .map_err(database_error)?;
+
+                return Err(attempts_exceeded());
+            }
+
+            return Err((
+                StatusCode::BAD_REQUEST,
+                ErrorResponse {
+                    message: "Verification code does not match".to_string(),
+                    code: "verification_code_mismatch",
+                    invalid_fields: Vec::new(),
+                },
+            ));
+        }
+
+        // A new code may have been requested in the meantime, which is left
+        // for its own attempts
+        let result = sqlx::query!(
+            "DELETE FROM phone_verifications WHERE senior_user_id = ? AND purpose = ? AND code = ?",
+            senior_user_id,
+            purpose,
+            verification.code
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+        if result.rows_affected() == 0 {
+            return Err(verification_expired());
+        }
+
+        Ok(verification.phone_hash)
+    }
+
+    /// Marks the phone number of the senior as verified if the code matches
+    /// the last one sent.
+    pub async fn confirm(
+        senior_user_id: UserId,
+        code: &str,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        let phone_hash =
+            Self::verify(senior_user_id, VerificationPurpose::PhoneVerification, code, pool)
+                .await?;
+
+        // The senior may have changed the phone number after the code was sent
+        let result = sqlx::query!(
+            "UPDATE senior_users SET phone_verified = TRUE WHERE id = ? AND phone_hash = ? AND deleted_at IS NULL",
+            senior_user_id,
+            phone_hash
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        match result.rows_affected() {
+            1.. => Ok(()),
+            _ => Err(verification_expired()),
+        }
+    }
+}
+
+fn attempts_exceeded() -> (StatusCode, ErrorResponse) {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        ErrorResponse {
+            message: "Too many wrong codes, request a new one".to_string(),
+            code: "verification_attempts_exceeded",
+            invalid_fields: Vec::new(),
+        },
+    )
+}
+
+/// Returns the error for the codes that do not exist or have expired.
+pub fn verification_expired() -> (StatusCode, ErrorResponse) {
+    (
+        StatusCode::BAD_REQUEST,
+        ErrorResponse {
+            message: "Verification code has expired".to_string(),
+            code: "verification_expired",
+            invalid_fields: Vec::new(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn normalizes_valid_numbers() {
+        for (phone, normalized) in [
+            ("010-1234-5678", "+821012345678"),
+            ("01012345678", "+821012345678"),
+            ("010 1234 5678", "+821012345678"),
+            ("(010) 1234.5678", "+821012345678"),
+            ("+82 10 1234 5678", "+821012345678"),
+            ("+82 010-1234-5678", "+821012345678"),
+            ("821012345678", "+821012345678"),
+            ("011-123-4567", "+82111234567"),
+            ("016-1234-5678", "+821612345678"),
+            ("019-123-4567", "+82191234567"),
+        ] {
+            assert_eq!(normalize(phone).unwrap(), normalized, "{phone}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_numbers() {
+        for phone in [
+            "",
+            "1012345678",
+            "010-123-4567",
+            "010-1234-56789",
+            "011-12-3456",
+            "011-1234-56789",
+            "02-1234-5678",
+            "012-1234-5678",
+            "010-1234-567a",
+            "+1 010 1234 5678",
+            "+82",
+        ] {
+            assert!(normalize(phone).is_err(), "{phone}");
+        }
+    }
+}
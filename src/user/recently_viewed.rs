@@ -0,0 +1,78 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use sqlx::MySql;
+
+use crate::{
+    error::database_error,
+    schema::{RecentlyViewedExportSchema, SeniorUserInfoSchema},
+    Result,
+};
+
+use super::account::{SeniorUser, UserId};
+
+/// Number of the recently viewed seniors kept for a normal user
+const RECENTLY_VIEWED_LIMIT: u32 = 20;
+
+/// Senior profiles the normal users looked at lately
+pub struct RecentlyViewed;
+
+impl RecentlyViewed {
+    /// Records that the normal user viewed the senior's profile just now.
+    pub async fn record(
+        user_id: UserId,
+        senior_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        // Viewing the same senior again only moves the senior to the front
+        sqlx::query!(
+            "INSERT INTO recently_viewed_seniors (normal_user_id, senior_user_id) VALUES (?, ?) ON DUPLICATE KEY UPDATE viewed_at = CURRENT_TIMESTAMP()",
+            user_id,
+            senior_id
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(())
+    }
+
+    /// Returns the seniors the normal user viewed lately, the latest first.
+    pub async fn get_all(
+        user_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<SeniorUserInfoSchema>> {
+        let mut query = sqlx::QueryBuilder::<MySql>::new(
+            "SELECT senior_users.* FROM recently_viewed_seniors \
+             JOIN senior_users ON senior_users.id = recently_viewed_seniors.senior_user_id \
+             WHERE senior_users.deleted_at IS NULL AND senior_users.dormant_at IS NULL \
+             AND recently_viewed_seniors.normal_user_id = ",
+        );
+        query
+            .push_bind(user_id)
+            .push(
+                " ORDER BY recently_viewed_seniors.viewed_at DESC, \
+                 recently_viewed_seniors.id DESC LIMIT ",
+            )
+            .push_bind(RECENTLY_VIEWED_LIMIT);
+
+        let seniors =
+            query.build_query_as::<SeniorUser>().fetch_all(pool).await.map_err(database_error)?;
+
+        Ok(seniors.into_iter().map(SeniorUserInfoSchema::from).collect())
+    }
+
+    /// Returns every recently viewed senior of the user for the data export.
+    pub async fn export(
+        user_id: UserId,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<Vec<RecentlyViewedExportSchema>> {
+        sqlx::query_as_unchecked!(
+            RecentlyViewedExportSchema,
+            "SELECT senior_user_id, viewed_at FROM recently_viewed_seniors WHERE normal_user_id = ? ORDER BY viewed_at DESC, id DESC",
+            user_id
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(database_error)
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use sqlx::MySql;
+
+use crate::{
+    crypto::FieldCipher,
+    error::database_error,
+    schema::{self, AccountRecoveryCompleteSchema, AccountRecoveryRequestSchema},
+    sms::SmsClient,
+    Result,
+};
+
+use super::{
+    account::{SeniorUser, User, UserId},
+    phone::{self, PhoneVerification, VerificationPurpose},
+};
+
+/// Events of the account recovery kept for the audit
+#[derive(Debug, Clone, Copy, sqlx::Type)]
+enum RecoveryEvent {
+    Requested,
+    Failed,
+    Completed,
+}
+
+/// Recovery of the senior accounts through the verified phone numbers, for the
+/// seniors who cannot use their email
+pub struct AccountRecovery;
+
+impl AccountRecovery {
+    /// Sends a recovery code to the verified phone number of the senior. It
+    /// succeeds without sending anything if the senior does not exist or has
+    /// no verified phone number, so the response does not reveal the accounts.
+    pub async fn request(
+        recovery_data: &AccountRecoveryRequestSchema,
+        cipher: &FieldCipher,
+        sms_client: &SmsClient,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        schema::validate(recovery_data)?;
+
+        let Some(user) = SeniorUser::find_by_email(&recovery_data.email, pool).await? else {
+            return Ok(());
+        };
+        if !user.phone_verified() {
+            return Ok(());
+        }
+
+        // The failures only happen for the existing accounts, such as the
+        // cooldown of the codes, so they are not reported either
+        if let Err((status, err)) = Self::send_code(&user, cipher, sms_client, pool).await {
+            tracing::warn!(
+                "Failed to send the recovery code to senior user {} ({}): {}",
+                user.id(),
+                status,
+                err.message
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn send_code(
+        user: &SeniorUser,
+        cipher: &FieldCipher,
+        sms_client: &SmsClient,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        PhoneVerification::send(
+            user.id(),
+            VerificationPurpose::AccountRecovery,
+            &user.phone(cipher)?,
+            cipher,
+            sms_client,
+            pool,
+        )
+        .await?;
+
+        Self::log_event(user.id(), RecoveryEvent::Requested, pool).await
+    }
+
+    /// Sets the new password of the senior if the recovery code matches.
+    pub async fn complete(
+        recovery_data: &AccountRecoveryCompleteSchema,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserId> {
+        // Checked before the code is used up, so that an invalid password does
+        // not waste it
+        schema::validate(recovery_data)?;
+
+        let user = SeniorUser::find_by_email(&recovery_data.email, pool)
+            .await?
+            .ok_or_else(phone::verification_expired)?;
+
+        if let Err(err) = PhoneVerification::verify(
+            user.id(),
+            VerificationPurpose::AccountRecovery,
+            &recovery_data.code,
+            pool,
+        )
+        .await
+        {
+            Self::log_event(user.id(), RecoveryEvent::Failed, pool).await?;
+            return Err(err);
+        }
+
+        let id = SeniorUser::reset_password(user.id(), &recovery_data.password, pool).await?;
+        Self::log_event(id, RecoveryEvent::Completed, pool).await?;
+
+        Ok(id)
+    }
+
+    async fn log_event(
+        senior_user_id: UserId,
+        event: RecoveryEvent,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<()> {
+        tracing::info!("Account recovery of senior user {}: {:?}", senior_user_id, event);
+
+        sqlx::query!(
+            "INSERT INTO recovery_audit_logs (senior_user_id, event) VALUES (?, ?)",
+            senior_user_id,
+            event
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2023. The resback authors all rights reserved.
+
+use std::str::FromStr;
+
+use axum::{async_trait, extract::multipart};
+use axum_typed_multipart::TypedMultipartError;
+use serde::{Deserialize, Serialize};
+use sqlx::MySql;
+
+use crate::{
+    error::database_error,
+    schema::{UserSettingsSchema, UserSettingsUpdateSchema},
+    Result,
+};
+
+use super::{account::UserId, UserType};
+
+/// Language of the messages we send to the users
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Locale {
+    Ko,
+    En,
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "ko" => Ok(Self::Ko),
+            "en" => Ok(Self::En),
+            _ => Err("Invalid locale string".to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl axum_typed_multipart::TryFromField for Locale {
+    async fn try_from_field(
+        field: multipart::Field<'_>,
+    ) -> std::result::Result<Self, TypedMultipartError> {
+        let field_name = field.name().unwrap_or("{unknown}").to_string();
+        let field_text = field.text().await?;
+
+        Ok(Locale::from_str(&field_text).map_err(|_| TypedMultipartError::WrongFieldType {
+            field_name,
+            wanted_type: "locale".to_string(),
+        })?)
+    }
+}
+
+/// Channel the users want to be contacted through
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+pub enum ContactChannel {
+    Email,
+    Sms,
+    Push,
+}
+
+impl FromStr for ContactChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "Email" => Ok(Self::Email),
+            "Sms" => Ok(Self::Sms),
+            "Push" => Ok(Self::Push),
+            _ => Err("Invalid contact channel string".to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl axum_typed_multipart::TryFromField for ContactChannel {
+    async fn try_from_field(
+        field: multipart::Field<'_>,
+    ) -> std::result::Result<Self, TypedMultipartError> {
+        let field_name = field.name().unwrap_or("{unknown}").to_string();
+        let field_text = field.text().await?;
+
+        Ok(ContactChannel::from_str(&field_text).map_err(|_| {
+            TypedMultipartError::WrongFieldType {
+                field_name,
+                wanted_type: "contact channel".to_string(),
+            }
+        })?)
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, utoipa::ToSchema,
+)]
+pub enum Theme {
+    /// Follows the setting of the device
+    System,
+    Light,
+    Dark,
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "System" => Ok(Self::System),
+            "Light" => Ok(Self::Light),
+            "Dark" => Ok(Self::Dark),
+            _ => Err("Invalid theme string".to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl axum_typed_multipart::TryFromField for Theme {
+    async fn try_from_field(
+        field: multipart::Field<'_>,
+    ) -> std::result::Result<Self, TypedMultipartError> {
+        let field_name = field.name().unwrap_or("{unknown}").to_string();
+        let field_text = field.text().await?;
+
+        Ok(Theme::from_str(&field_text).map_err(|_| TypedMultipartError::WrongFieldType {
+            field_name,
+            wanted_type: "theme".to_string(),
+        })?)
+    }
+}
+
+/// Preferences of the users shared by the both user types
+pub struct UserSettings;
+
+impl UserSettings {
+    /// Returns the settings of the user. The users who have never saved their
+    /// settings get the default ones.
+    pub async fn get(
+        user: (UserType, UserId),
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserSettingsSchema> {
+        let settings = sqlx::query_as_unchecked!(
+            UserSettingsSchema,
+            "SELECT email_notification, sms_notification, push_notification, contact_channel, language, theme FROM user_settings WHERE user_type = ? AND user_id = ?",
+            user.0,
+            user.1
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(settings.unwrap_or_default())
+    }
+
+    /// Returns the language the messages to the user are written in.
+    pub async fn locale(user: (UserType, UserId), pool: &sqlx::Pool<MySql>) -> Result<Locale> {
+        Self::get(user, pool).await.map(|settings| settings.language)
+    }
+
+    /// Replaces the settings of the user.
+    pub async fn update(
+        user: (UserType, UserId),
+        settings: &UserSettingsUpdateSchema,
+        pool: &sqlx::Pool<MySql>,
+    ) -> Result<UserSettingsSchema> {
+        sqlx::query!(
+            "INSERT INTO user_settings (user_type, user_id, email_notification, sms_notification, push_notification, contact_channel, language, theme) VALUES (?, ?, ?, ?, ?, ?, ?, ?) ON DUPLICATE KEY UPDATE email_notification = VALUES(email_notification), sms_notification = VALUES(sms_notification), push_notification = VALUES(push_notification), contact_channel = VALUES(contact_channel), language = VALUES(language), theme = VALUES(theme)",
+            user.0,
+            user.1,
+            settings.email_notification,
+            settings.sms_notification,
+            settings.push_notification,
+            settings.contact_channel,
+            settings.language,
+            settings.theme
+        )
+        .execute(pool)
+        .await
+        .map_err(database_error)?;
+
+        Ok(UserSettingsSchema {
+            email_notification: settings.email_notification,
+            sms_notification: settings.sms_notification,
+            push_notification: settings.push_notification,
+            contact_channel: settings.contact_channel,
+            language: settings.language,
+            theme: settings.theme,
+        })
+    }
+}
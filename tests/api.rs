@@ -33,3 +33,266 @@ Authors: {:?}
 
     assert_eq!(&body[..], &about[..]);
 }
+
+#[sqlx::test]
+async fn get_seniors_empty_page(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    let response = app
+        .oneshot(Request::builder().uri("/users/senior?limit=5").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(&body[..], br#"{"data":[],"meta":{"next_cursor":null}}"#);
+}
+
+#[sqlx::test]
+async fn get_majors_without_seniors(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    let response =
+        app.oneshot(Request::builder().uri("/majors").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let categories = response["data"].as_array().unwrap();
+    assert!(!categories.is_empty());
+    assert!(categories.iter().all(|category| category["senior_count"] == 0));
+}
+
+#[sqlx::test]
+async fn error_as_problem_details(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/users/normal/1")
+                .header("x-request-id", "test-request")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.headers()["content-type"], "application/problem+json");
+    assert_eq!(response.headers()["x-request-id"], "test-request");
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(problem["type"], "https://respec.team/problems/invalid_oauth_user");
+    assert_eq!(problem["title"], "Bad Request");
+    assert_eq!(problem["status"], 400);
+    assert_eq!(problem["instance"], "test-request");
+    assert_eq!(problem["code"], "invalid_oauth_user");
+}
+
+#[sqlx::test]
+async fn error_message_localized(pool: Pool<MySql>) {
+    for (accept_language, detail) in [
+        (None, "잘못된 OAuth 사용자 정보입니다"),
+        (Some("en-US,en;q=0.9,ko;q=0.8"), "Invalid OAuth user data"),
+        (Some("fr,ko;q=0.5"), "잘못된 OAuth 사용자 정보입니다"),
+    ] {
+        let app = app(&Config::default(), &pool);
+
+        let mut request = Request::builder().uri("/users/normal/1");
+        if let Some(accept_language) = accept_language {
+            request = request.header("accept-language", accept_language);
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["detail"], detail);
+    }
+}
+
+#[sqlx::test]
+async fn get_seniors_invalid_price(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/users/senior?min_price=-1&max_price=-1")
+                .header("accept-language", "en")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(problem["code"], "validation_failed");
+    assert_eq!(problem["detail"], "Invalid fields: max_price, min_price");
+    assert_eq!(problem["invalid_fields"], serde_json::json!(["max_price", "min_price"]));
+}
+
+#[sqlx::test]
+async fn get_seniors_invalid_price_default_locale(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/users/senior?min_price=-1&max_price=-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(problem["code"], "validation_failed");
+    assert_eq!(problem["detail"], "올바르지 않은 입력값이 있습니다");
+    assert_eq!(problem["invalid_fields"], serde_json::json!(["max_price", "min_price"]));
+}
+
+#[sqlx::test]
+async fn json_request_body(pool: Pool<MySql>) {
+    for (body, status, code) in [
+        (r#"{"email":"not an email"}"#, StatusCode::UNPROCESSABLE_ENTITY, "validation_failed"),
+        (r#"{"email":"#, StatusCode::BAD_REQUEST, "invalid_body"),
+    ] {
+        let app = app(&Config::default(), &pool);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/auth/senior/recovery")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], code);
+    }
+}
+
+#[sqlx::test]
+async fn openapi_document(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    let response = app
+        .oneshot(Request::builder().uri("/api-docs/openapi.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(document["paths"]["/users/senior/{id}"]["get"].is_object());
+    assert!(document["components"]["schemas"]["ProblemDetails"].is_object());
+}
+
+#[sqlx::test]
+async fn verification_rate_limited(pool: Pool<MySql>) {
+    let app = app(&Config::default(), &pool);
+
+    for attempt in 0..6 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/auth/senior/recovery")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"email":"not an email"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        if attempt < 5 {
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+            continue;
+        }
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], "rate_limited");
+    }
+}
+
+#[sqlx::test]
+async fn verification_attempts_capped(pool: Pool<MySql>) {
+    let id = sqlx::query("INSERT INTO senior_users (email, password, name, phone, phone_hash, nickname, picture, major, experience_years, mentoring_price, representative_careers, description) VALUES ('senior@example.com', '', '시니어', '', 'hash', '시니어', '', '백엔드', 1, 10000, '[]', '')")
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_id();
+    // One attempt is left for the code
+    sqlx::query("INSERT INTO phone_verifications (senior_user_id, purpose, phone_hash, code, attempts, expires_at) VALUES (?, 'AccountRecovery', 'hash', '123456', 4, CURRENT_TIMESTAMP() + INTERVAL 300 SECOND)")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = app(&Config::default(), &pool);
+
+    // The right code is not accepted any more once the last attempt is used
+    for (code, status, error_code) in [
+        ("000000", StatusCode::TOO_MANY_REQUESTS, "verification_attempts_exceeded"),
+        ("123456", StatusCode::BAD_REQUEST, "verification_expired"),
+    ] {
+        let body =
+            format!(r#"{{"email":"senior@example.com","code":"{}","password":"password"}}"#, code);
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/auth/senior/recovery/confirm")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], error_code);
+    }
+
+    let pending: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM phone_verifications")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(pending, 0);
+}
+
+#[sqlx::test]
+async fn delete_user_requires_login(pool: Pool<MySql>) {
+    for uri in ["/users/senior/1", "/users/normal/1"] {
+        let app = app(&Config::default(), &pool);
+
+        let response = app
+            .oneshot(Request::builder().method("DELETE").uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["code"], "not_logged_in");
+    }
+}